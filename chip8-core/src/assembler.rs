@@ -0,0 +1,643 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error, fmt,
+};
+
+use crate::{
+    emulator::{PROG_END, PROG_START},
+    instruction::Instruction,
+};
+
+/// An error in the assembly source, reported with the line it occurred on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl error::Error for AssembleError {}
+
+/// Assembles Octo-style source into a ROM to be loaded at `PROG_START`.
+///
+/// Supported are the mnemonics printed by the disassembler, labels (`: name`),
+/// `:const name value`, `:alias name vX`, bare numbers as data bytes and bare
+/// label names as subroutine calls, along with `if ... then` and
+/// `loop ... again` with `while`. Comments start with `#`.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssembleError> {
+    let tokens = tokenize(source);
+
+    // The first pass only works out where each label ends up, so that labels
+    // can be used before they are defined.
+    let mut first_pass = Assembler::new(&tokens, HashMap::new(), false);
+    first_pass.run()?;
+
+    let mut second_pass = Assembler::new(&tokens, first_pass.symbols, true);
+    second_pass.run()?;
+    Ok(second_pass.output)
+}
+
+/// Words with a meaning of their own, which can't be used as names.
+const KEYWORDS: &[&str] = &[
+    "again", "begin", "else", "end", "if", "key", "-key", "loop", "then", "while",
+];
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    line: usize,
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    source
+        .lines()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            let code = line.split('#').next().unwrap_or("");
+            code.split_whitespace().map(move |text| Token {
+                text,
+                line: index + 1,
+            })
+        })
+        .collect()
+}
+
+struct Assembler<'a> {
+    tokens: &'a [Token<'a>],
+    position: usize,
+    /// Labels and constants.
+    symbols: HashMap<&'a str, i32>,
+    /// Names defined so far in this pass, to catch duplicates.
+    defined: HashSet<&'a str>,
+    aliases: HashMap<&'a str, u8>,
+    /// Whether every name must already be known. Off for the first pass.
+    resolve: bool,
+    output: Vec<u8>,
+    /// The line of the most recently read token.
+    line: usize,
+    /// The `loop`s waiting for their `again`, innermost last.
+    loops: Vec<Loop>,
+}
+
+struct Loop {
+    /// The address `again` jumps back to.
+    start: u16,
+    /// The line of the `loop`, for reporting it if `again` is missing.
+    line: usize,
+    /// The offsets of the jumps emitted by `while`, which leave the loop
+    /// once `again` says where it ends.
+    exits: Vec<usize>,
+}
+
+impl<'a> Assembler<'a> {
+    fn new(
+        tokens: &'a [Token<'a>],
+        symbols: HashMap<&'a str, i32>,
+        resolve: bool,
+    ) -> Assembler<'a> {
+        Assembler {
+            tokens,
+            position: 0,
+            symbols,
+            defined: HashSet::new(),
+            aliases: HashMap::new(),
+            resolve,
+            output: Vec::new(),
+            line: 1,
+            loops: Vec::new(),
+        }
+    }
+
+    fn run(&mut self) -> Result<(), AssembleError> {
+        while self.position < self.tokens.len() {
+            self.statement()?;
+        }
+        match self.loops.last() {
+            Some(open) => Err(AssembleError {
+                line: open.line,
+                message: String::from("'loop' has no matching 'again'."),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn statement(&mut self) -> Result<(), AssembleError> {
+        let token = self.next()?;
+        match token.text {
+            ":" => {
+                let name = self.name()?;
+                let address = self.address();
+                self.define(name, address)
+            }
+            ":const" => {
+                let name = self.name()?;
+                let value = self.value()?;
+                self.define(name, value)
+            }
+            ":alias" => {
+                let name = self.name()?;
+                let register = self.register()?;
+                self.aliases.insert(name.text, register);
+                Ok(())
+            }
+            ":call" => {
+                let address = self.operand_address()?;
+                self.emit(Instruction::Call(address))
+            }
+            "jump" => {
+                let address = self.operand_address()?;
+                self.emit(Instruction::Jump(address))
+            }
+            "jump0" => {
+                let address = self.operand_address()?;
+                self.emit(Instruction::JumpOffset(address))
+            }
+            "clear" => self.emit(Instruction::Clear),
+            "return" => self.emit(Instruction::Return),
+            "scroll-right" => self.emit(Instruction::ScrollRight),
+            "scroll-left" => self.emit(Instruction::ScrollLeft),
+            "exit" => self.emit(Instruction::Exit),
+            "lores" => self.emit(Instruction::Lores),
+            "hires" => self.emit(Instruction::Hires),
+            "audio" => self.emit(Instruction::Audio),
+            "scroll-down" => {
+                let n = self.nibble()?;
+                self.emit(Instruction::ScrollDown(n))
+            }
+            "scroll-up" => {
+                let n = self.nibble()?;
+                self.emit(Instruction::ScrollUp(n))
+            }
+            "plane" => {
+                let n = self.nibble()?;
+                self.emit(Instruction::Plane(n))
+            }
+            "sprite" => {
+                let x = self.register()?;
+                let y = self.register()?;
+                let n = self.nibble()?;
+                self.emit(Instruction::Draw(x, y, n))
+            }
+            "bcd" => {
+                let x = self.register()?;
+                self.emit(Instruction::Bcd(x))
+            }
+            "saveflags" => {
+                let x = self.register()?;
+                self.emit(Instruction::SaveFlags(x))
+            }
+            "loadflags" => {
+                let x = self.register()?;
+                self.emit(Instruction::LoadFlags(x))
+            }
+            "save" | "load" => {
+                let x = self.register()?;
+                let y = if self.peek() == Some("-") {
+                    self.position += 1;
+                    Some(self.register()?)
+                } else {
+                    None
+                };
+                self.emit(match (token.text, y) {
+                    ("save", None) => Instruction::Save(x),
+                    ("save", Some(y)) => Instruction::SaveRange(x, y),
+                    (_, None) => Instruction::Load(x),
+                    (_, Some(y)) => Instruction::LoadRange(x, y),
+                })
+            }
+            "delay" => {
+                self.expect(":=")?;
+                let x = self.register()?;
+                self.emit(Instruction::SetDelay(x))
+            }
+            "buzzer" => {
+                self.expect(":=")?;
+                let x = self.register()?;
+                self.emit(Instruction::SetSound(x))
+            }
+            "pitch" => {
+                self.expect(":=")?;
+                let x = self.register()?;
+                self.emit(Instruction::Pitch(x))
+            }
+            "i" => self.i_statement(),
+            "if" => self.if_statement(),
+            "loop" => {
+                let start = self.jump_target(token, self.address())?;
+                self.loops.push(Loop {
+                    start,
+                    line: token.line,
+                    exits: Vec::new(),
+                });
+                Ok(())
+            }
+            "while" => self.while_statement(token),
+            "again" => self.again_statement(token),
+            "begin" | "else" | "end" => Err(error(
+                token,
+                &format!(
+                    "'{}' blocks are not supported; use 'if ... then' with a single statement.",
+                    token.text
+                ),
+            )),
+            _ if KEYWORDS.contains(&token.text) => Err(error(
+                token,
+                &format!("'{}' can't start a statement.", token.text),
+            )),
+            _ if token.text.starts_with(':') => Err(error(
+                token,
+                &format!("Unknown directive '{}'.", token.text),
+            )),
+            _ if self.is_register(token) => self.register_statement(token),
+            _ if is_number(token.text) => {
+                let byte = self.byte_value(token)?;
+                self.push(&[byte])
+            }
+            // A bare label is a call in Octo.
+            _ => {
+                let address = self.address_value(token)?;
+                self.emit(Instruction::Call(address))
+            }
+        }
+    }
+
+    fn i_statement(&mut self) -> Result<(), AssembleError> {
+        let operator = self.next()?;
+        match operator.text {
+            ":=" => match self.peek() {
+                Some("long") => {
+                    self.position += 1;
+                    let token = self.next()?;
+                    let address = self.ranged_value(token, 0, 0xFFFF, "address")?;
+                    self.emit(Instruction::SetLongI)?;
+                    self.push(&(address as u16).to_be_bytes())
+                }
+                Some("hex") => {
+                    self.position += 1;
+                    let x = self.register()?;
+                    self.emit(Instruction::Hex(x))
+                }
+                Some("bighex") => {
+                    self.position += 1;
+                    let x = self.register()?;
+                    self.emit(Instruction::BigHex(x))
+                }
+                _ => {
+                    let address = self.operand_address()?;
+                    self.emit(Instruction::SetI(address))
+                }
+            },
+            "+=" => {
+                let x = self.register()?;
+                self.emit(Instruction::AddI(x))
+            }
+            _ => Err(error(operator, "Expected ':=' or '+=' after 'i'.")),
+        }
+    }
+
+    fn if_statement(&mut self) -> Result<(), AssembleError> {
+        self.condition()?;
+        if self.peek() == Some("begin") {
+            let token = self.next()?;
+            return Err(error(
+                token,
+                "'if ... begin' blocks are not supported; use 'if ... then' with a single statement.",
+            ));
+        }
+        self.expect("then")
+    }
+
+    /// `while` leaves the innermost loop unless its condition holds.
+    fn while_statement(&mut self, token: Token<'a>) -> Result<(), AssembleError> {
+        if self.loops.is_empty() {
+            return Err(error(token, "'while' must be inside a 'loop'."));
+        }
+        self.condition()?;
+        let exit = self.output.len();
+        // The target is filled in by `again`.
+        self.emit(Instruction::Jump(0))?;
+        self.loops
+            .last_mut()
+            .expect("To be inside a loop.")
+            .exits
+            .push(exit);
+        Ok(())
+    }
+
+    fn again_statement(&mut self, token: Token<'a>) -> Result<(), AssembleError> {
+        let open = self
+            .loops
+            .pop()
+            .ok_or_else(|| error(token, "'again' has no matching 'loop'."))?;
+        self.emit(Instruction::Jump(open.start))?;
+        let end = self.jump_target(token, self.address())?;
+        let jump = Instruction::Jump(end).encode().to_be_bytes();
+        for exit in open.exits {
+            self.output[exit..exit + 2].copy_from_slice(&jump);
+        }
+        Ok(())
+    }
+
+    /// Emits a condition for `if` or `while`, which only runs the following
+    /// instruction when it holds. Each comparison becomes the skip
+    /// instruction for its opposite. Like Octo, `<`, `>`, `<=` and `>=`
+    /// subtract in VF and then test the borrow flag, so they overwrite VF.
+    fn condition(&mut self) -> Result<(), AssembleError> {
+        let x = self.register()?;
+        let operator = self.next()?;
+        match operator.text {
+            "key" => self.emit(Instruction::SkipIfNotKey(x)),
+            "-key" => self.emit(Instruction::SkipIfKey(x)),
+            "==" | "!=" => {
+                let operand = self.next()?;
+                let is_equal = operator.text == "==";
+                self.emit(if self.is_register(operand) {
+                    let y = self.register_value(operand)?;
+                    if is_equal {
+                        Instruction::SkipIfRegistersNotEqual(x, y)
+                    } else {
+                        Instruction::SkipIfRegistersEqual(x, y)
+                    }
+                } else {
+                    let nn = self.byte_value(operand)?;
+                    if is_equal {
+                        Instruction::SkipIfNotEqual(x, nn)
+                    } else {
+                        Instruction::SkipIfEqual(x, nn)
+                    }
+                })
+            }
+            "<" | ">" | "<=" | ">=" => {
+                let operand = self.next()?;
+                self.emit(if self.is_register(operand) {
+                    Instruction::Set(0xF, self.register_value(operand)?)
+                } else {
+                    Instruction::SetConst(0xF, self.byte_value(operand)?)
+                })?;
+                // VF is left as 1 when the subtraction doesn't borrow.
+                self.emit(match operator.text {
+                    // VF := operand - VX, which borrows when VX > operand.
+                    ">" | "<=" => Instruction::Sub(0xF, x),
+                    // VF := VX - operand, which borrows when VX < operand.
+                    _ => Instruction::SubReversed(0xF, x),
+                })?;
+                self.emit(match operator.text {
+                    ">" | "<" => Instruction::SkipIfEqual(0xF, 1),
+                    _ => Instruction::SkipIfNotEqual(0xF, 1),
+                })
+            }
+            _ => Err(error(
+                operator,
+                &format!(
+                    "Unknown comparison '{}'. Expected '==', '!=', '<', '>', '<=', '>=', 'key' or '-key'.",
+                    operator.text
+                ),
+            )),
+        }
+    }
+
+    fn register_statement(&mut self, register: Token<'a>) -> Result<(), AssembleError> {
+        let x = self.register_value(register)?;
+        let operator = self.next()?;
+        let operand = self.next()?;
+
+        let instruction = match (operator.text, operand.text) {
+            (":=", "random") => {
+                let nn = self.byte()?;
+                Instruction::Random(x, nn)
+            }
+            (":=", "delay") => Instruction::GetDelay(x),
+            (":=", "key") => Instruction::WaitKey(x),
+            (":=", _) if self.is_register(operand) => {
+                Instruction::Set(x, self.register_value(operand)?)
+            }
+            (":=", _) => Instruction::SetConst(x, self.byte_value(operand)?),
+            ("+=", _) if self.is_register(operand) => {
+                Instruction::Add(x, self.register_value(operand)?)
+            }
+            ("+=", _) => Instruction::AddConst(x, self.byte_value(operand)?),
+            ("-=", _) => Instruction::Sub(x, self.register_value(operand)?),
+            ("=-", _) => Instruction::SubReversed(x, self.register_value(operand)?),
+            ("|=", _) => Instruction::Or(x, self.register_value(operand)?),
+            ("&=", _) => Instruction::And(x, self.register_value(operand)?),
+            ("^=", _) => Instruction::Xor(x, self.register_value(operand)?),
+            (">>=", _) => Instruction::ShiftRight(x, self.register_value(operand)?),
+            ("<<=", _) => Instruction::ShiftLeft(x, self.register_value(operand)?),
+            _ => {
+                return Err(error(
+                    operator,
+                    &format!("Unknown register operator '{}'.", operator.text),
+                ))
+            }
+        };
+        self.emit(instruction)
+    }
+
+    fn emit(&mut self, instruction: Instruction) -> Result<(), AssembleError> {
+        self.push(&instruction.encode().to_be_bytes())
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> Result<(), AssembleError> {
+        if self.output.len() + bytes.len() > PROG_END - PROG_START {
+            return Err(AssembleError {
+                line: self.line,
+                message: String::from("The program is too large to fit in memory."),
+            });
+        }
+        self.output.extend(bytes);
+        Ok(())
+    }
+
+    fn define(&mut self, name: Token<'a>, value: i32) -> Result<(), AssembleError> {
+        if !self.defined.insert(name.text) {
+            return Err(error(name, &format!("'{}' is already defined.", name.text)));
+        }
+        self.symbols.insert(name.text, value);
+        Ok(())
+    }
+
+    /// The address the next byte will be loaded at.
+    fn address(&self) -> i32 {
+        (PROG_START + self.output.len()) as i32
+    }
+
+    fn next(&mut self) -> Result<Token<'a>, AssembleError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                self.line = token.line;
+                Ok(*token)
+            }
+            None => Err(AssembleError {
+                line: self.line,
+                message: String::from("Unexpected end of input."),
+            }),
+        }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(|token| token.text)
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), AssembleError> {
+        let token = self.next()?;
+        if token.text != text {
+            return Err(error(
+                token,
+                &format!("Expected '{}' but found '{}'.", text, token.text),
+            ));
+        }
+        Ok(())
+    }
+
+    fn name(&mut self) -> Result<Token<'a>, AssembleError> {
+        let token = self.next()?;
+        if is_number(token.text) || self.is_register(token) || KEYWORDS.contains(&token.text) {
+            return Err(error(
+                token,
+                &format!("'{}' can't be used as a name.", token.text),
+            ));
+        }
+        Ok(token)
+    }
+
+    fn register(&mut self) -> Result<u8, AssembleError> {
+        let token = self.next()?;
+        self.register_value(token)
+    }
+
+    fn nibble(&mut self) -> Result<u8, AssembleError> {
+        let token = self.next()?;
+        Ok(self.ranged_value(token, 0, 0xF, "nibble")? as u8)
+    }
+
+    fn byte(&mut self) -> Result<u8, AssembleError> {
+        let token = self.next()?;
+        self.byte_value(token)
+    }
+
+    fn operand_address(&mut self) -> Result<u16, AssembleError> {
+        let token = self.next()?;
+        self.address_value(token)
+    }
+
+    fn value(&mut self) -> Result<i32, AssembleError> {
+        let token = self.next()?;
+        self.token_value(token)
+    }
+
+    fn is_register(&self, token: Token) -> bool {
+        self.aliases.contains_key(token.text) || parse_register(token.text).is_some()
+    }
+
+    fn register_value(&self, token: Token) -> Result<u8, AssembleError> {
+        self.aliases
+            .get(token.text)
+            .copied()
+            .or_else(|| parse_register(token.text))
+            .ok_or_else(|| {
+                error(
+                    token,
+                    &format!("Expected a register but found '{}'.", token.text),
+                )
+            })
+    }
+
+    /// Bytes may also be written as negative numbers, which are stored in two's complement.
+    fn byte_value(&self, token: Token) -> Result<u8, AssembleError> {
+        Ok(self.ranged_value(token, -128, 0xFF, "byte")? as u8)
+    }
+
+    fn address_value(&self, token: Token) -> Result<u16, AssembleError> {
+        Ok(self.ranged_value(token, 0, 0xFFF, "address")? as u16)
+    }
+
+    /// Checks that `address` can be reached by a jump.
+    fn jump_target(&self, token: Token, address: i32) -> Result<u16, AssembleError> {
+        if address > 0xFFF {
+            return Err(error(
+                token,
+                &format!(
+                    "'{}' is past the last address a jump can reach.",
+                    token.text
+                ),
+            ));
+        }
+        Ok(address as u16)
+    }
+
+    fn ranged_value(
+        &self,
+        token: Token,
+        min: i32,
+        max: i32,
+        kind: &str,
+    ) -> Result<i32, AssembleError> {
+        let value = self.token_value(token)?;
+        if !(min..=max).contains(&value) {
+            return Err(error(
+                token,
+                &format!("'{}' is out of range for a {}.", token.text, kind),
+            ));
+        }
+        Ok(value)
+    }
+
+    fn token_value(&self, token: Token) -> Result<i32, AssembleError> {
+        if is_number(token.text) {
+            return parse_number(token.text)
+                .ok_or_else(|| error(token, &format!("Invalid number '{}'.", token.text)));
+        }
+        match self.symbols.get(token.text) {
+            Some(value) => Ok(*value),
+            // Names defined further down are only known in the second pass.
+            None if !self.resolve => Ok(0),
+            None => Err(error(token, &format!("'{}' is not defined.", token.text))),
+        }
+    }
+}
+
+fn error(token: Token, message: &str) -> AssembleError {
+    AssembleError {
+        line: token.line,
+        message: String::from(message),
+    }
+}
+
+fn is_number(text: &str) -> bool {
+    text.trim_start_matches('-')
+        .starts_with(|c: char| c.is_ascii_digit())
+}
+
+fn parse_number(text: &str) -> Option<i32> {
+    let (is_negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let value = if let Some(hex) = digits.strip_prefix("0x") {
+        i32::from_str_radix(hex, 16)
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        i32::from_str_radix(binary, 2)
+    } else {
+        digits.parse()
+    }
+    .ok()?;
+    Some(if is_negative { -value } else { value })
+}
+
+fn parse_register(text: &str) -> Option<u8> {
+    let digit = text.strip_prefix('v')?;
+    if digit.len() != 1 {
+        return None;
+    }
+    u8::from_str_radix(digit, 16).ok()
+}
+
+#[cfg(test)]
+#[path = "./assembler_test.rs"]
+mod assembler_test;
@@ -0,0 +1,256 @@
+use super::{assemble, AssembleError};
+use crate::{
+    emulator::{Chip8, StepOutcome},
+    instruction::Instruction,
+    quirks::Quirks,
+};
+
+#[test]
+fn assembles_labels_and_forward_references() {
+    let source = "
+        : main
+            clear
+            draw          # a bare label is a call
+            jump main
+
+        : draw
+            i := sprite
+            sprite v0 v1 2
+            return
+
+        : sprite
+            0x3C 0b01000010
+    ";
+    assert_eq!(
+        assemble(source).unwrap(),
+        [
+            0x00, 0xE0, // clear
+            0x22, 0x06, // call draw
+            0x12, 0x00, // jump main
+            0xA2, 0x0C, // i := sprite
+            0xD0, 0x12, // sprite v0 v1 2
+            0x00, 0xEE, // return
+            0x3C, 0x42, // sprite data
+        ]
+    );
+}
+
+#[test]
+fn assembles_constants_and_aliases() {
+    let source = "
+        :const SPEED 3
+        :alias x v4
+        x := SPEED
+        x += -1
+        if x != SPEED then x := v1
+    ";
+    assert_eq!(
+        assemble(source).unwrap(),
+        [0x64, 0x03, 0x74, 0xFF, 0x34, 0x03, 0x84, 0x10]
+    );
+}
+
+#[test]
+fn assembles_conditions_as_opposite_skips() {
+    let source = "
+        if v1 == 5 then exit
+        if v1 != v2 then exit
+        if v3 key then exit
+        if v3 -key then exit
+    ";
+    assert_eq!(
+        assemble(source).unwrap(),
+        [
+            0x41, 0x05, 0x00, 0xFD, 0x51, 0x20, 0x00, 0xFD, 0xE3, 0xA1, 0x00, 0xFD, 0xE3, 0x9E,
+            0x00, 0xFD,
+        ]
+    );
+}
+
+#[test]
+fn assembles_comparisons_through_vf() {
+    let source = "
+        if v1 > 5 then exit
+        if v1 < v2 then exit
+        if v1 >= 5 then exit
+        if v1 <= v2 then exit
+    ";
+    assert_eq!(
+        assemble(source).unwrap(),
+        [
+            0x6F, 0x05, 0x8F, 0x15, 0x3F, 0x01, 0x00, 0xFD, // >
+            0x8F, 0x20, 0x8F, 0x17, 0x3F, 0x01, 0x00, 0xFD, // <
+            0x6F, 0x05, 0x8F, 0x17, 0x4F, 0x01, 0x00, 0xFD, // >=
+            0x8F, 0x20, 0x8F, 0x15, 0x4F, 0x01, 0x00, 0xFD, // <=
+        ]
+    );
+}
+
+#[test]
+fn comparisons_hold_for_the_right_values() {
+    for operator in ["==", "!=", "<", ">", "<=", ">="] {
+        for (a, b) in [(3, 5), (5, 5), (7, 5), (0, 255), (255, 0)] {
+            let expected = match operator {
+                "==" => a == b,
+                "!=" => a != b,
+                "<" => a < b,
+                ">" => a > b,
+                "<=" => a <= b,
+                _ => a >= b,
+            };
+            for operand in ["v1", "b"] {
+                let source = format!(
+                    ":const b {b}  v0 := {a}  v1 := b  v2 := 0  if v0 {operator} {operand} then v2 := 1  exit"
+                );
+                let mut chip8 = Chip8::new(Quirks::default());
+                chip8.load(&assemble(&source).unwrap()).unwrap();
+                while chip8.cycle() != Ok(StepOutcome::Exited) {}
+                assert_eq!(chip8.registers()[2] == 1, expected, "{}", source);
+            }
+        }
+    }
+}
+
+#[test]
+fn assembles_loops() {
+    let source = "
+        loop
+            v0 += 1
+            while v0 != 10
+            v1 += 2
+        again
+        exit
+    ";
+    assert_eq!(
+        assemble(source).unwrap(),
+        [
+            0x70, 0x01, // v0 += 1
+            0x30, 0x0A, // while v0 != 10
+            0x12, 0x0A, // leave the loop
+            0x71, 0x02, // v1 += 2
+            0x12, 0x00, // again
+            0x00, 0xFD, // exit
+        ]
+    );
+}
+
+#[test]
+fn assembles_nested_loops() {
+    let source = "
+        loop
+            loop
+                while v0 == 1
+            again
+            while v1 == 1
+        again
+    ";
+    assert_eq!(
+        assemble(source).unwrap(),
+        [
+            0x40, 0x01, 0x12, 0x06, 0x12, 0x00, // inner loop
+            0x41, 0x01, 0x12, 0x0C, 0x12, 0x00, // outer loop
+        ]
+    );
+}
+
+#[test]
+fn assembles_long_i() {
+    assert_eq!(
+        assemble("i := long data : data 0xAA").unwrap(),
+        [0xF0, 0x00, 0x02, 0x04, 0xAA]
+    );
+}
+
+#[test]
+fn assembles_every_disassembled_instruction() {
+    for opcode in 0..=u16::MAX {
+        let instruction = match Instruction::decode(opcode) {
+            Ok(Instruction::SetLongI) | Err(_) => continue,
+            Ok(instruction) => instruction,
+        };
+        assert_eq!(
+            assemble(&instruction.to_string()),
+            Ok(opcode.to_be_bytes().to_vec()),
+            "{}",
+            instruction
+        );
+    }
+}
+
+#[test]
+fn reports_undefined_names() {
+    assert_eq!(
+        assemble("clear\njump nowhere"),
+        Err(AssembleError {
+            line: 2,
+            message: String::from("'nowhere' is not defined."),
+        })
+    );
+}
+
+#[test]
+fn reports_duplicate_labels() {
+    assert_eq!(assemble(": main\n: main").unwrap_err().line, 2);
+}
+
+#[test]
+fn reports_unmatched_loops() {
+    assert_eq!(
+        assemble("loop\nv0 += 1"),
+        Err(AssembleError {
+            line: 1,
+            message: String::from("'loop' has no matching 'again'."),
+        })
+    );
+    assert_eq!(
+        assemble("v0 += 1\nagain"),
+        Err(AssembleError {
+            line: 2,
+            message: String::from("'again' has no matching 'loop'."),
+        })
+    );
+    assert_eq!(
+        assemble("while v0 == 1"),
+        Err(AssembleError {
+            line: 1,
+            message: String::from("'while' must be inside a 'loop'."),
+        })
+    );
+}
+
+#[test]
+fn reports_unsupported_keywords() {
+    let message = |source| assemble(source).unwrap_err().message;
+    assert_eq!(
+        message("if v0 == 1 begin"),
+        "'if ... begin' blocks are not supported; use 'if ... then' with a single statement."
+    );
+    assert_eq!(
+        message("else"),
+        "'else' blocks are not supported; use 'if ... then' with a single statement."
+    );
+    assert_eq!(
+        message("if v0 =! 1 then"),
+        "Unknown comparison '=!'. Expected '==', '!=', '<', '>', '<=', '>=', 'key' or '-key'."
+    );
+    assert_eq!(message("then"), "'then' can't start a statement.");
+    assert_eq!(message(":macro twice"), "Unknown directive ':macro'.");
+    assert_eq!(message(": loop"), "'loop' can't be used as a name.");
+}
+
+#[test]
+fn reports_out_of_range_values() {
+    assert_eq!(
+        assemble("v0 := 256"),
+        Err(AssembleError {
+            line: 1,
+            message: String::from("'256' is out of range for a byte."),
+        })
+    );
+    assert!(assemble("jump 0x1000").is_err());
+}
+
+#[test]
+fn reports_unexpected_end_of_input() {
+    assert_eq!(assemble("\nsprite v0").unwrap_err().line, 2);
+}
@@ -0,0 +1,87 @@
+pub const PATTERN_SIZE: usize = 16;
+
+/// The default pitch, which plays the pattern at 4000 bits per second.
+const DEFAULT_PITCH: u8 = 64;
+/// A square wave used until a ROM loads its own pattern with `F002`.
+const DEFAULT_PATTERN: [u8; PATTERN_SIZE] = [0xF0; PATTERN_SIZE];
+#[cfg(feature = "std")]
+const VOLUME: f32 = 0.25;
+
+/// The XO-CHIP audio registers: a 128-bit pattern played one bit at a time
+/// at a rate controlled by the pitch register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioState {
+    pub pattern: [u8; PATTERN_SIZE],
+    pub pitch: u8,
+}
+
+impl AudioState {
+    pub fn new() -> AudioState {
+        AudioState {
+            pattern: DEFAULT_PATTERN,
+            pitch: DEFAULT_PITCH,
+        }
+    }
+
+    /// The number of pattern bits played per second.
+    #[cfg(feature = "std")]
+    pub fn playback_rate(&self) -> f32 {
+        4000.0 * f32::powf(2.0, (self.pitch as f32 - 64.0) / 48.0)
+    }
+
+    #[cfg(feature = "std")]
+    fn bit(&self, index: usize) -> bool {
+        self.pattern[index / 8] & (0x80 >> (index % 8)) != 0
+    }
+}
+
+impl Default for AudioState {
+    fn default() -> Self {
+        AudioState::new()
+    }
+}
+
+/// Converts an `AudioState` into samples, keeping track of the position
+/// within the pattern so that consecutive buffers join up seamlessly.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct SampleGenerator {
+    position: f32,
+}
+
+#[cfg(feature = "std")]
+impl SampleGenerator {
+    pub fn new() -> SampleGenerator {
+        SampleGenerator { position: 0.0 }
+    }
+
+    /// Fills `buffer` with mono samples at `sample_rate`. Silence is written
+    /// when `is_playing` is false.
+    pub fn fill(
+        &mut self,
+        audio: &AudioState,
+        is_playing: bool,
+        buffer: &mut [f32],
+        sample_rate: u32,
+    ) {
+        if !is_playing {
+            buffer.fill(0.0);
+            return;
+        }
+
+        let pattern_bits = (PATTERN_SIZE * 8) as f32;
+        let step = audio.playback_rate() / sample_rate as f32;
+        for sample in buffer.iter_mut() {
+            *sample = if audio.bit(self.position as usize) {
+                VOLUME
+            } else {
+                -VOLUME
+            };
+            self.position = (self.position + step) % pattern_bits;
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./audio_test.rs"]
+mod audio_test;
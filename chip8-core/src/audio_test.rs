@@ -0,0 +1,29 @@
+use super::{AudioState, SampleGenerator, VOLUME};
+
+#[test]
+fn playback_rate_follows_pitch() {
+    let mut audio = AudioState::new();
+    assert_eq!(audio.playback_rate(), 4000.0);
+    audio.pitch = 112;
+    assert_eq!(audio.playback_rate(), 8000.0);
+}
+
+#[test]
+fn fills_pattern_bits() {
+    let mut audio = AudioState::new();
+    audio.pattern = [0; 16];
+    audio.pattern[0] = 0b1010_0000;
+    let mut generator = SampleGenerator::new();
+    let mut buffer = [0.0; 5];
+    generator.fill(&audio, true, &mut buffer, 4000);
+    assert_eq!(buffer, [VOLUME, -VOLUME, VOLUME, -VOLUME, -VOLUME]);
+}
+
+#[test]
+fn fills_silence_when_not_playing() {
+    let audio = AudioState::new();
+    let mut generator = SampleGenerator::new();
+    let mut buffer = [1.0; 4];
+    generator.fill(&audio, false, &mut buffer, 4000);
+    assert_eq!(buffer, [0.0; 4]);
+}
@@ -0,0 +1,27 @@
+use std::{env, fs, process};
+
+use chip8_core::assembler;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (source_path, output_path) = match args.as_slice() {
+        [source_path, output_path] => (source_path, output_path),
+        _ => {
+            eprintln!("Usage: chip8-asm <source.8o> <output.ch8>");
+            process::exit(2);
+        }
+    };
+
+    let source = fs::read_to_string(source_path).unwrap_or_else(|error| {
+        eprintln!("Unable to read {}: {}", source_path, error);
+        process::exit(1);
+    });
+    let rom = assembler::assemble(&source).unwrap_or_else(|error| {
+        eprintln!("{}: {}", source_path, error);
+        process::exit(1);
+    });
+    if let Err(error) = fs::write(output_path, rom) {
+        eprintln!("Unable to write {}: {}", output_path, error);
+        process::exit(1);
+    }
+}
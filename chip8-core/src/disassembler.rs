@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{emulator::PROG_START, instruction::Instruction};
+
+/// The most data bytes printed on a single line.
+const DATA_BYTES_PER_LINE: usize = 4;
+
+/// Produces an Octo-style listing of a ROM loaded at `PROG_START`.
+///
+/// Bytes are only treated as code if they are reachable by following control
+/// flow from `PROG_START`; everything else is printed as data. Jump, call and
+/// `jump0` targets inside the ROM are given labels. An instruction with code
+/// or a label inside it, such as the operand of `i := long`, is printed as
+/// data after a comment so that nothing inside it is lost.
+pub fn disassemble(rom: &[u8]) -> String {
+    let code = find_reachable_code(rom);
+    let labels = generate_labels(rom, &code);
+
+    let mut listing = String::new();
+    let mut offset = 0;
+    while offset < rom.len() {
+        let address = address_of(offset);
+        if let Some(label) = labels.get(&address) {
+            listing += &format!(": {}\n", label);
+        }
+
+        let instruction = code.get(&address);
+        let overlap = instruction.and_then(|instruction| {
+            find_overlap(offset, instruction.size() as usize, &code, &labels)
+        });
+        match instruction {
+            Some(instruction) if overlap.is_none() => {
+                let size = instruction.size() as usize;
+                let raw = rom[offset..offset + size]
+                    .chunks(2)
+                    .map(|word| format!("{:02X}{:02X}", word[0], word[1]))
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                let mnemonic =
+                    format_instruction(instruction, &rom[offset..offset + size], &labels);
+                listing += &format!("{:04X}  {:<9}  {}\n", address, raw, mnemonic);
+                offset += size;
+            }
+            _ => {
+                if let (Some(instruction), Some(overlap)) = (instruction, overlap) {
+                    // Octo can't express instructions that overlap, so the outer one is
+                    // kept as data to leave room for the code or label inside it.
+                    let size = instruction.size() as usize;
+                    let mnemonic =
+                        format_instruction(instruction, &rom[offset..offset + size], &labels);
+                    listing += &format!(
+                        "# {:04X} is inside {}, which is listed as data\n",
+                        overlap, mnemonic
+                    );
+                }
+                // Gather data up to the next instruction or label.
+                let mut end = offset + 1;
+                while end < rom.len()
+                    && end - offset < DATA_BYTES_PER_LINE
+                    && !code.contains_key(&address_of(end))
+                    && !labels.contains_key(&address_of(end))
+                {
+                    end += 1;
+                }
+                let bytes = &rom[offset..end];
+                let raw = bytes
+                    .iter()
+                    .map(|byte| format!("{:02X}", byte))
+                    .collect::<String>();
+                let values = bytes
+                    .iter()
+                    .map(|byte| format!("{:#04X}", byte))
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                listing += &format!("{:04X}  {:<9}  {}\n", address, raw, values);
+                offset = end;
+            }
+        }
+    }
+
+    listing
+}
+
+/// Follows every path of execution from `PROG_START`, returning the decoded
+/// instruction at each reachable address.
+fn find_reachable_code(rom: &[u8]) -> BTreeMap<u16, Instruction> {
+    let mut code = BTreeMap::new();
+    let mut pending = VecDeque::from([PROG_START as u16]);
+
+    while let Some(address) = pending.pop_front() {
+        if code.contains_key(&address) {
+            continue;
+        }
+        let instruction = match decode_at(rom, address) {
+            Some(instruction) => instruction,
+            None => continue,
+        };
+        code.insert(address, instruction);
+
+        let next = u16::wrapping_add(address, instruction.size());
+        match instruction {
+            Instruction::Return | Instruction::Exit => {}
+            Instruction::Jump(target) => pending.push_back(target),
+            Instruction::JumpOffset(target) => pending.push_back(target),
+            Instruction::Call(target) => {
+                pending.push_back(target);
+                pending.push_back(next);
+            }
+            Instruction::SkipIfEqual(..)
+            | Instruction::SkipIfNotEqual(..)
+            | Instruction::SkipIfRegistersEqual(..)
+            | Instruction::SkipIfRegistersNotEqual(..)
+            | Instruction::SkipIfKey(..)
+            | Instruction::SkipIfNotKey(..) => {
+                pending.push_back(next);
+                if let Some(skipped) = decode_at(rom, next) {
+                    pending.push_back(u16::wrapping_add(next, skipped.size()));
+                }
+            }
+            _ => pending.push_back(next),
+        }
+    }
+
+    code
+}
+
+fn generate_labels(rom: &[u8], code: &BTreeMap<u16, Instruction>) -> BTreeMap<u16, String> {
+    let rom_range = PROG_START..PROG_START + rom.len();
+    let calls = code.values().filter_map(|instruction| match *instruction {
+        Instruction::Call(target) => Some(target),
+        _ => None,
+    });
+    let jumps = code.values().filter_map(|instruction| match *instruction {
+        Instruction::Jump(target) | Instruction::JumpOffset(target) => Some(target),
+        _ => None,
+    });
+
+    // Calls are named first so that a location that is both called and jumped to reads as a subroutine.
+    let mut labels = BTreeMap::new();
+    for target in calls.filter(|target| rom_range.contains(&(*target as usize))) {
+        labels.insert(target, format!("sub_{:04X}", target));
+    }
+    for target in jumps.filter(|target| rom_range.contains(&(*target as usize))) {
+        labels
+            .entry(target)
+            .or_insert_with(|| format!("label_{:04X}", target));
+    }
+    labels
+}
+
+fn format_instruction(
+    instruction: &Instruction,
+    bytes: &[u8],
+    labels: &BTreeMap<u16, String>,
+) -> String {
+    match *instruction {
+        Instruction::Jump(target) if labels.contains_key(&target) => {
+            format!("jump {}", labels[&target])
+        }
+        // A bare label is a call in Octo.
+        Instruction::Call(target) if labels.contains_key(&target) => labels[&target].clone(),
+        Instruction::JumpOffset(target) if labels.contains_key(&target) => {
+            format!("jump0 {}", labels[&target])
+        }
+        Instruction::SetLongI => {
+            format!(
+                "{} {:#06X}",
+                instruction,
+                (bytes[2] as u16) << 8 | bytes[3] as u16
+            )
+        }
+        _ => instruction.to_string(),
+    }
+}
+
+/// The first address inside the instruction at `offset`, past its first byte,
+/// that is reachable code or has a label.
+fn find_overlap(
+    offset: usize,
+    size: usize,
+    code: &BTreeMap<u16, Instruction>,
+    labels: &BTreeMap<u16, String>,
+) -> Option<u16> {
+    (offset + 1..offset + size)
+        .map(address_of)
+        .find(|address| code.contains_key(address) || labels.contains_key(address))
+}
+
+fn decode_at(rom: &[u8], address: u16) -> Option<Instruction> {
+    let offset = (address as usize).checked_sub(PROG_START)?;
+    let opcode = (*rom.get(offset)? as u16) << 8 | *rom.get(offset + 1)? as u16;
+    let instruction = Instruction::decode(opcode).ok()?;
+    // The operand of a long instruction has to be inside the ROM as well.
+    if offset + instruction.size() as usize > rom.len() {
+        return None;
+    }
+    Some(instruction)
+}
+
+fn address_of(offset: usize) -> u16 {
+    (PROG_START + offset) as u16
+}
+
+#[cfg(test)]
+#[path = "./disassembler_test.rs"]
+mod disassembler_test;
@@ -0,0 +1,67 @@
+use super::disassemble;
+
+#[test]
+fn disassembles_reachable_code() {
+    let rom = [
+        0x00, 0xE0, // clear
+        0x22, 0x0A, // call 0x20A
+        0x12, 0x04, // jump 0x204
+        0x3C, 0x42, // data
+        0x99, // data
+        0x00, // data
+        0xA2, 0x06, // i := 0x206
+        0xF0, 0x00, 0x12, 0x34, // i := long 0x1234
+        0x00, 0xEE, // return
+    ];
+    assert_eq!(
+        disassemble(&rom),
+        "\
+0200  00E0       clear
+0202  220A       sub_020A
+: label_0204
+0204  1204       jump label_0204
+0206  3C429900   0x3C 0x42 0x99 0x00
+: sub_020A
+020A  A206       i := 0x206
+020C  F000 1234  i := long 0x1234
+0210  00EE       return
+"
+    );
+}
+
+#[test]
+fn follows_both_sides_of_skips() {
+    let rom = [
+        0x30, 0x01, // if v0 != 0x01 then
+        0xF0, 0x00, 0x12, 0x34, // i := long 0x1234
+        0x00, 0xFD, // exit
+        0xFF, 0xFF, // data
+    ];
+    assert_eq!(
+        disassemble(&rom),
+        "\
+0200  3001       if v0 != 0x01 then
+0202  F000 1234  i := long 0x1234
+0206  00FD       exit
+0208  FFFF       0xFF 0xFF
+"
+    );
+}
+
+#[test]
+fn keeps_code_inside_long_operands() {
+    let rom = [
+        0xF0, 0x00, 0x00, 0xE0, // i := long 0x00E0, or clear from 0x202
+        0x12, 0x02, // jump 0x202
+    ];
+    assert_eq!(
+        disassemble(&rom),
+        "\
+# 0202 is inside i := long 0x00E0, which is listed as data
+0200  F000       0xF0 0x00
+: label_0202
+0202  00E0       clear
+0204  1202       jump label_0202
+"
+    );
+}
@@ -0,0 +1,144 @@
+use core::ops::{Index, IndexMut};
+
+use crate::save_state::{StateError, StateReader, StateWriter};
+
+pub const LORES_WIDTH: usize = 64;
+pub const LORES_HEIGHT: usize = 32;
+pub const HIRES_WIDTH: usize = 128;
+pub const HIRES_HEIGHT: usize = 64;
+
+/// The bitmask selecting both XO-CHIP drawing planes.
+pub const ALL_PLANES: u8 = 0b11;
+
+/// The framebuffer. Each pixel is a bitmask of the XO-CHIP planes it is lit
+/// on, so plain CHIP-8 programs only ever produce 0 or 1. In low resolution
+/// mode only the top-left `LORES_WIDTH` x `LORES_HEIGHT` pixels are in use.
+///
+/// Rows can be indexed directly, e.g. `display[y][x]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Display {
+    hires: bool,
+    pixels: [[u8; HIRES_WIDTH]; HIRES_HEIGHT],
+}
+
+impl Display {
+    pub fn new() -> Display {
+        Display {
+            hires: false,
+            pixels: [[0; HIRES_WIDTH]; HIRES_HEIGHT],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        if self.hires {
+            HIRES_WIDTH
+        } else {
+            LORES_WIDTH
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        if self.hires {
+            HIRES_HEIGHT
+        } else {
+            LORES_HEIGHT
+        }
+    }
+
+    /// Switches resolution. Like SUPER-CHIP, this also clears the screen.
+    pub fn set_hires(&mut self, hires: bool) {
+        self.hires = hires;
+        self.clear(ALL_PLANES);
+    }
+
+    /// Clears the given planes.
+    pub fn clear(&mut self, planes: u8) {
+        for pixel in self.pixels.iter_mut().flatten() {
+            *pixel &= !planes;
+        }
+    }
+
+    /// Iterates over the visible rows for the current resolution.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        let width = self.width();
+        self.pixels[..self.height()]
+            .iter()
+            .map(move |row| &row[..width])
+    }
+
+    pub fn scroll_down(&mut self, planes: u8, count: usize) {
+        self.scroll(planes, 0, count as isize);
+    }
+
+    pub fn scroll_up(&mut self, planes: u8, count: usize) {
+        self.scroll(planes, 0, -(count as isize));
+    }
+
+    pub fn scroll_right(&mut self, planes: u8, count: usize) {
+        self.scroll(planes, count as isize, 0);
+    }
+
+    pub fn scroll_left(&mut self, planes: u8, count: usize) {
+        self.scroll(planes, -(count as isize), 0);
+    }
+
+    /// The bytes written by `write_state`.
+    pub(crate) const STATE_SIZE: usize = 1 + HIRES_WIDTH * HIRES_HEIGHT;
+
+    pub(crate) fn write_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.hires);
+        for row in self.pixels.iter() {
+            writer.bytes(row);
+        }
+    }
+
+    pub(crate) fn read_state(reader: &mut StateReader) -> Result<Display, StateError> {
+        let hires = reader.bool()?;
+        let mut pixels = [[0; HIRES_WIDTH]; HIRES_HEIGHT];
+        for row in pixels.iter_mut() {
+            *row = reader.array()?;
+        }
+        Ok(Display { hires, pixels })
+    }
+
+    /// Moves the given planes by (dx, dy), filling the exposed area with unlit pixels.
+    fn scroll(&mut self, planes: u8, dx: isize, dy: isize) {
+        let source = self.pixels;
+        let width = self.width() as isize;
+        let height = self.height() as isize;
+        for y in 0..height {
+            for x in 0..width {
+                let (source_x, source_y) = (x - dx, y - dy);
+                let moved = if (0..width).contains(&source_x) && (0..height).contains(&source_y) {
+                    source[source_y as usize][source_x as usize] & planes
+                } else {
+                    0
+                };
+                let pixel = &mut self.pixels[y as usize][x as usize];
+                *pixel = (*pixel & !planes) | moved;
+            }
+        }
+    }
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Display::new()
+    }
+}
+
+impl Index<usize> for Display {
+    type Output = [u8];
+
+    fn index(&self, y: usize) -> &[u8] {
+        &self.pixels[..self.height()][y][..self.width()]
+    }
+}
+
+impl IndexMut<usize> for Display {
+    fn index_mut(&mut self, y: usize) -> &mut [u8] {
+        let width = self.width();
+        let height = self.height();
+        &mut self.pixels[..height][y][..width]
+    }
+}
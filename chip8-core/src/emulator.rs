@@ -0,0 +1,1125 @@
+use core::{fmt, ops::Range};
+
+use crate::{
+    audio::{AudioState, PATTERN_SIZE},
+    display::{Display, ALL_PLANES},
+    host::Host,
+    instruction::Instruction,
+    quirks::{LoadStoreIncrement, Quirks},
+    random::{RandomSource, SplitMix64},
+    save_state::{StateError, StateReader, StateWriter, OVERHEAD_SIZE},
+    watchpoint::{Access, RegisterSnapshot, Watchpoints},
+};
+
+/// The result of successfully executing a single instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    /// The instruction was executed and the PC advanced.
+    Executed,
+    /// `FX0A` is blocking until a key is pressed; the PC did not advance.
+    WaitingForKey,
+    /// `DXYN` is blocking until the next 60 Hz tick; the PC did not advance.
+    WaitingForDisplay,
+    /// `00FD` was executed and the program has exited; the PC did not advance.
+    Exited,
+    /// The instruction was executed and triggered a watchpoint set to break.
+    HitWatchpoint,
+}
+
+/// The result of running a frame with `run_frame`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// The outcome of the last instruction run, or `Executed` if there were none.
+    pub outcome: StepOutcome,
+    /// Whether anything was drawn during the frame.
+    pub display_changed: bool,
+}
+
+/// What has changed since the last call to `take_writes`, so that rewinding
+/// can record an instruction without serializing all of memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Writes {
+    /// Covers every byte of memory written, and is empty if there were none.
+    pub memory: Range<usize>,
+    pub display: bool,
+    /// The number of 60 Hz ticks.
+    pub frames: u32,
+}
+
+impl Writes {
+    #[cfg(feature = "std")]
+    const NONE: Writes = Writes {
+        memory: 0..0,
+        display: false,
+        frames: 0,
+    };
+
+    /// Everything in the machine, e.g. after loading a state.
+    const ALL: Writes = Writes {
+        memory: 0..MEMORY_SIZE,
+        display: true,
+        frames: 0,
+    };
+
+    fn memory(&mut self, start: usize, length: usize) {
+        let end = start + length;
+        self.memory = if self.memory.is_empty() {
+            start..end
+        } else {
+            self.memory.start.min(start)..self.memory.end.max(end)
+        };
+    }
+}
+
+/// An error raised while executing an instruction. The machine is left at the
+/// faulting instruction so that frontends can inspect its state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionError {
+    /// The PC (or PC + 1) points outside of memory.
+    PcOutOfBounds { pc: u16 },
+    /// The opcode does not match any known instruction.
+    UnknownOpcode { pc: u16, opcode: u16 },
+    /// `2NNN` was executed with a full stack.
+    StackOverflow { pc: u16, opcode: u16 },
+    /// `00EE` was executed with an empty stack.
+    StackUnderflow { pc: u16, opcode: u16 },
+    /// An instruction tried to access memory outside of the address space via I.
+    MemoryOutOfBounds {
+        pc: u16,
+        opcode: u16,
+        address: usize,
+    },
+    /// `FX29`/`FX30` was executed with a VX that is not a hex digit.
+    InvalidDigit {
+        pc: u16,
+        opcode: u16,
+        register: u8,
+        value: u8,
+    },
+    /// `EX9E`/`EXA1` was executed with a VX that is not a key.
+    InvalidKey {
+        pc: u16,
+        opcode: u16,
+        register: u8,
+        value: u8,
+    },
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ExecutionError::PcOutOfBounds { pc } => write!(f, "PC {:#05X} is out of bounds", pc),
+            ExecutionError::UnknownOpcode { pc, opcode } => {
+                write!(f, "Unknown opcode {:#06X} at {:#05X}", opcode, pc)
+            }
+            ExecutionError::StackOverflow { pc, opcode } => {
+                write!(f, "Stack overflow by {:#06X} at {:#05X}", opcode, pc)
+            }
+            ExecutionError::StackUnderflow { pc, opcode } => {
+                write!(f, "Stack underflow by {:#06X} at {:#05X}", opcode, pc)
+            }
+            ExecutionError::MemoryOutOfBounds {
+                pc,
+                opcode,
+                address,
+            } => write!(
+                f,
+                "Memory address {:#X} is out of bounds for {:#06X} at {:#05X}",
+                address, opcode, pc
+            ),
+            ExecutionError::InvalidDigit {
+                pc,
+                opcode,
+                register,
+                value,
+            } => write!(
+                f,
+                "V{:X} = {} is not a hex digit for {:#06X} at {:#05X}",
+                register, value, opcode, pc
+            ),
+            ExecutionError::InvalidKey {
+                pc,
+                opcode,
+                register,
+                value,
+            } => write!(
+                f,
+                "V{:X} = {} is not a key for {:#06X} at {:#05X}",
+                register, value, opcode, pc
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ExecutionError {}
+
+pub struct Chip8<H: Host = (), R: RandomSource = SplitMix64> {
+    reg_pc: u16,
+    reg_sp: u8,
+    reg_i: u16,
+    reg_timer_delay: u8,
+    reg_timer_sound: u8,
+    reg_v: [u8; 16],
+    reg_flags: [u8; 16],
+
+    stack: [u16; STACK_SIZE],
+    memory: [u8; MEMORY_SIZE],
+    display: Display,
+    cycles_per_frame: u16,
+    frame_cycles: u16,
+    quirks: Quirks,
+    vblank_ready: bool,
+    /// The key `FX0A` saw pressed, which it waits for to be released.
+    pressed_key: Option<u8>,
+    planes: u8,
+    audio: AudioState,
+    watchpoints: Watchpoints,
+    rng: R,
+    /// Set when the display changes, until the frontend clears it.
+    display_dirty: bool,
+    /// Set when the display changes during the current frame.
+    display_changed: bool,
+    /// The sound last reported to the host.
+    sound: Option<AudioState>,
+    writes: Writes,
+
+    host: H,
+}
+
+impl Chip8 {
+    /// Creates a machine with no keys pressed and whose `CXNN` results are the
+    /// same on every run. Use `with_host` to connect it to a frontend.
+    pub fn new(quirks: Quirks) -> Chip8 {
+        Chip8::with_rng(quirks, SplitMix64::default())
+    }
+}
+
+impl<R: RandomSource> Chip8<(), R> {
+    pub fn with_rng(quirks: Quirks, rng: R) -> Chip8<(), R> {
+        Chip8::with_host((), quirks, rng)
+    }
+}
+
+impl<H: Host, R: RandomSource> Chip8<H, R> {
+    pub fn with_host(host: H, quirks: Quirks, rng: R) -> Chip8<H, R> {
+        Chip8 {
+            reg_pc: PROG_START as u16,
+            reg_sp: 0,
+            reg_i: 0,
+            reg_timer_delay: 0,
+            reg_timer_sound: 0,
+            reg_v: [0; 16],
+            reg_flags: [0; 16],
+
+            stack: [0; STACK_SIZE],
+            memory: initialize_memory(),
+            display: Display::new(),
+            cycles_per_frame: DEFAULT_CYCLES_PER_FRAME,
+            frame_cycles: 0,
+            quirks,
+            vblank_ready: true,
+            pressed_key: None,
+            planes: 1,
+            audio: AudioState::new(),
+            watchpoints: Watchpoints::new(),
+            rng,
+            display_dirty: true,
+            display_changed: false,
+            sound: None,
+            writes: Writes::ALL,
+
+            host,
+        }
+    }
+
+    pub fn load(&mut self, data: &[u8]) -> Result<(), &str> {
+        if data.len() > PROG_END - PROG_START {
+            return Err("ROM data is too large for memory.");
+        }
+
+        self.memory[PROG_START..PROG_START + data.len()].copy_from_slice(data);
+        self.writes.memory(PROG_START, data.len());
+        Ok(())
+    }
+
+    /// Sets how many instructions make up one 60 Hz frame. After that many
+    /// cycles the timers tick on their own. With 0, they only tick when
+    /// `tick_60hz` is called.
+    pub fn set_cycles_per_frame(&mut self, cycles_per_frame: u16) {
+        self.cycles_per_frame = cycles_per_frame;
+    }
+
+    pub fn cycles_per_frame(&self) -> u16 {
+        self.cycles_per_frame
+    }
+
+    /// Advances the machine by one 60 Hz frame: both timers count down and a
+    /// `DXYN` waiting for the display may run.
+    pub fn tick_60hz(&mut self) {
+        self.reg_timer_delay = u8::saturating_sub(self.reg_timer_delay, 1);
+        self.reg_timer_sound = u8::saturating_sub(self.reg_timer_sound, 1);
+        self.frame_cycles = 0;
+        self.vblank_ready = true;
+        self.writes.frames = self.writes.frames.saturating_add(1);
+        self.update_sound();
+    }
+
+    /// Runs a 60 Hz frame: up to `cycles` instructions followed by a tick of
+    /// the timers. The frame ends early if an instruction doesn't simply
+    /// execute, e.g. while waiting for a key. On an error, the timers are left
+    /// untouched and the instructions already run stay run.
+    pub fn run_frame(&mut self, cycles: u16) -> Result<Frame, ExecutionError> {
+        self.run_frame_with(cycles, |_| {})
+    }
+
+    /// Like `run_frame`, calling `after_step` after each instruction.
+    pub(crate) fn run_frame_with(
+        &mut self,
+        cycles: u16,
+        mut after_step: impl FnMut(&mut Chip8<H, R>),
+    ) -> Result<Frame, ExecutionError> {
+        self.display_changed = false;
+        let mut outcome = StepOutcome::Executed;
+        for _ in 0..cycles {
+            outcome = self.step()?;
+            after_step(self);
+            if outcome != StepOutcome::Executed {
+                break;
+            }
+        }
+        self.tick_60hz();
+        Ok(Frame {
+            outcome,
+            display_changed: self.display_changed,
+        })
+    }
+
+    /// Runs one instruction, ticking the timers once every `cycles_per_frame`.
+    pub fn cycle(&mut self) -> Result<StepOutcome, ExecutionError> {
+        let outcome = self.step()?;
+        if self.cycles_per_frame > 0 {
+            self.frame_cycles = u16::saturating_add(self.frame_cycles, 1);
+            if self.frame_cycles >= self.cycles_per_frame {
+                self.tick_60hz();
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Runs one instruction without advancing the timers.
+    pub fn step(&mut self) -> Result<StepOutcome, ExecutionError> {
+        let pc = self.reg_pc;
+        let opcode = get_opcode(&self.memory, pc).ok_or(ExecutionError::PcOutOfBounds { pc })?;
+        let instruction = Instruction::decode(opcode)
+            .map_err(|_| ExecutionError::UnknownOpcode { pc, opcode })?;
+
+        self.watchpoints.clear_hits();
+        if self.watchpoints.is_empty() {
+            return self.execute(pc, opcode, instruction);
+        }
+
+        let access = self.memory_access(instruction);
+        let before = self.register_snapshot();
+        let outcome = self.execute(pc, opcode, instruction)?;
+        if outcome != StepOutcome::Executed {
+            return Ok(outcome);
+        }
+
+        let after = self.register_snapshot();
+        self.watchpoints
+            .check(pc, access, &self.memory, &before, &after);
+        if self.watchpoints.should_break() {
+            return Ok(StepOutcome::HitWatchpoint);
+        }
+        Ok(outcome)
+    }
+
+    fn mark_display_changed(&mut self) {
+        self.writes.display = true;
+        self.display_dirty = true;
+        self.display_changed = true;
+        self.host.display_changed(&self.display);
+    }
+
+    /// Tells the host if the sound to play has changed.
+    fn update_sound(&mut self) {
+        let sound = self.should_play_sound().then_some(self.audio);
+        if sound != self.sound {
+            self.sound = sound;
+            self.host.set_sound(sound.as_ref());
+        }
+    }
+
+    fn execute(
+        &mut self,
+        pc: u16,
+        opcode: u16,
+        instruction: Instruction,
+    ) -> Result<StepOutcome, ExecutionError> {
+        // Jumps and skips change where execution continues; everything else falls through.
+        let mut next_pc = u16::wrapping_add(pc, instruction.size());
+
+        match instruction {
+            Instruction::ScrollDown(n) => {
+                self.display.scroll_down(self.planes, n as usize);
+                self.mark_display_changed();
+            }
+            Instruction::ScrollUp(n) => {
+                self.display.scroll_up(self.planes, n as usize);
+                self.mark_display_changed();
+            }
+            Instruction::Clear => {
+                self.display.clear(self.planes);
+                self.mark_display_changed();
+            }
+            Instruction::Return => {
+                self.reg_sp = u8::checked_sub(self.reg_sp, 1)
+                    .ok_or(ExecutionError::StackUnderflow { pc, opcode })?;
+                let return_pc = *self
+                    .stack
+                    .get(self.reg_sp as usize)
+                    .ok_or(ExecutionError::StackUnderflow { pc, opcode })?;
+                next_pc = u16::wrapping_add(return_pc, 2);
+            }
+            Instruction::ScrollRight => {
+                self.display.scroll_right(self.planes, 4);
+                self.mark_display_changed();
+            }
+            Instruction::ScrollLeft => {
+                self.display.scroll_left(self.planes, 4);
+                self.mark_display_changed();
+            }
+            Instruction::Exit => return Ok(StepOutcome::Exited),
+            Instruction::Lores | Instruction::Hires => {
+                self.display.set_hires(instruction == Instruction::Hires);
+                self.mark_display_changed();
+            }
+            Instruction::Jump(address) => next_pc = address,
+            Instruction::Call(address) => {
+                if self.reg_sp >= STACK_SIZE as u8 {
+                    return Err(ExecutionError::StackOverflow { pc, opcode });
+                }
+                self.stack[self.reg_sp as usize] = pc;
+                self.reg_sp += 1;
+                next_pc = address;
+            }
+            Instruction::SkipIfEqual(x, value) => {
+                if self.reg_v[x as usize] == value {
+                    next_pc = self.skip_instruction_at(next_pc);
+                }
+            }
+            Instruction::SkipIfNotEqual(x, value) => {
+                if self.reg_v[x as usize] != value {
+                    next_pc = self.skip_instruction_at(next_pc);
+                }
+            }
+            Instruction::SkipIfRegistersEqual(x, y) => {
+                if self.reg_v[x as usize] == self.reg_v[y as usize] {
+                    next_pc = self.skip_instruction_at(next_pc);
+                }
+            }
+            Instruction::SaveRange(x, y) => {
+                let (first, last) = (x as usize, y as usize);
+                let count = usize::abs_diff(first, last) + 1;
+                self.check_memory_range(pc, opcode, self.reg_i as usize, count)?;
+                for offset in 0..count {
+                    let index = if first <= last {
+                        first + offset
+                    } else {
+                        first - offset
+                    };
+                    self.memory[self.reg_i as usize + offset] = self.reg_v[index];
+                }
+                self.writes.memory(self.reg_i as usize, count);
+            }
+            Instruction::LoadRange(x, y) => {
+                let (first, last) = (x as usize, y as usize);
+                let count = usize::abs_diff(first, last) + 1;
+                self.check_memory_range(pc, opcode, self.reg_i as usize, count)?;
+                for offset in 0..count {
+                    let index = if first <= last {
+                        first + offset
+                    } else {
+                        first - offset
+                    };
+                    self.reg_v[index] = self.memory[self.reg_i as usize + offset];
+                }
+            }
+            Instruction::SetConst(x, value) => self.reg_v[x as usize] = value,
+            Instruction::AddConst(x, value) => {
+                self.reg_v[x as usize] = u8::wrapping_add(self.reg_v[x as usize], value);
+            }
+            Instruction::Set(x, y) => self.reg_v[x as usize] = self.reg_v[y as usize],
+            Instruction::Or(x, y) => {
+                self.reg_v[x as usize] |= self.reg_v[y as usize];
+                if self.quirks.vf_reset {
+                    self.reg_v[15] = 0;
+                }
+            }
+            Instruction::And(x, y) => {
+                self.reg_v[x as usize] &= self.reg_v[y as usize];
+                if self.quirks.vf_reset {
+                    self.reg_v[15] = 0;
+                }
+            }
+            Instruction::Xor(x, y) => {
+                self.reg_v[x as usize] ^= self.reg_v[y as usize];
+                if self.quirks.vf_reset {
+                    self.reg_v[15] = 0;
+                }
+            }
+            Instruction::Add(x, y) => {
+                let (new_value, did_overflow) =
+                    u8::overflowing_add(self.reg_v[x as usize], self.reg_v[y as usize]);
+                self.reg_v[x as usize] = new_value;
+                self.reg_v[15] = if did_overflow { 1 } else { 0 };
+            }
+            Instruction::Sub(x, y) => {
+                let (new_value, did_overflow) =
+                    u8::overflowing_sub(self.reg_v[x as usize], self.reg_v[y as usize]);
+                self.reg_v[x as usize] = new_value;
+                self.reg_v[15] = if did_overflow { 0 } else { 1 };
+            }
+            Instruction::ShiftRight(x, y) => {
+                let value = if self.quirks.shift_uses_vy {
+                    self.reg_v[y as usize]
+                } else {
+                    self.reg_v[x as usize]
+                };
+                self.reg_v[x as usize] = value >> 1;
+                self.reg_v[15] = value & 1;
+            }
+            Instruction::SubReversed(x, y) => {
+                let (new_value, did_overflow) =
+                    u8::overflowing_sub(self.reg_v[y as usize], self.reg_v[x as usize]);
+                self.reg_v[x as usize] = new_value;
+                self.reg_v[15] = if did_overflow { 0 } else { 1 };
+            }
+            Instruction::ShiftLeft(x, y) => {
+                let value = if self.quirks.shift_uses_vy {
+                    self.reg_v[y as usize]
+                } else {
+                    self.reg_v[x as usize]
+                };
+                self.reg_v[x as usize] = value << 1;
+                self.reg_v[15] = value >> 7;
+            }
+            Instruction::SkipIfRegistersNotEqual(x, y) => {
+                if self.reg_v[x as usize] != self.reg_v[y as usize] {
+                    next_pc = self.skip_instruction_at(next_pc);
+                }
+            }
+            Instruction::SetI(address) => self.reg_i = address,
+            Instruction::JumpOffset(address) => {
+                let index = if self.quirks.jump_uses_vx {
+                    (address >> 8) as usize
+                } else {
+                    0
+                };
+                next_pc = address + self.reg_v[index] as u16;
+            }
+            Instruction::Random(x, mask) => {
+                self.reg_v[x as usize] = self.rng.next_byte() & mask;
+            }
+            Instruction::Draw(x, y, n) => {
+                if let Some(outcome) = self.draw(pc, opcode, x, y, n)? {
+                    return Ok(outcome);
+                }
+            }
+            Instruction::SkipIfKey(x) => {
+                if self.is_key_pressed(pc, opcode, x as usize)? {
+                    next_pc = self.skip_instruction_at(next_pc);
+                }
+            }
+            Instruction::SkipIfNotKey(x) => {
+                if !self.is_key_pressed(pc, opcode, x as usize)? {
+                    next_pc = self.skip_instruction_at(next_pc);
+                }
+            }
+            Instruction::SetLongI => {
+                let address = u16::wrapping_add(pc, 2);
+                self.reg_i =
+                    get_opcode(&self.memory, address).ok_or(ExecutionError::MemoryOutOfBounds {
+                        pc,
+                        opcode,
+                        address: address as usize,
+                    })?;
+            }
+            Instruction::Plane(planes) => self.planes = planes & ALL_PLANES,
+            Instruction::Audio => {
+                let start = self.reg_i as usize;
+                self.check_memory_range(pc, opcode, start, PATTERN_SIZE)?;
+                self.audio
+                    .pattern
+                    .copy_from_slice(&self.memory[start..start + PATTERN_SIZE]);
+                self.update_sound();
+            }
+            Instruction::GetDelay(x) => self.reg_v[x as usize] = self.reg_timer_delay,
+            // As on the COSMAC VIP, the key is only taken once it's released.
+            Instruction::WaitKey(x) => match self.pressed_key {
+                Some(key) if !self.host.is_key_pressed(key) => {
+                    self.reg_v[x as usize] = key;
+                    self.pressed_key = None;
+                }
+                _ => {
+                    if self.pressed_key.is_none() {
+                        self.pressed_key = (0..16).find(|key| self.host.is_key_pressed(*key));
+                    }
+                    self.host.waiting_for_key();
+                    return Ok(StepOutcome::WaitingForKey);
+                }
+            },
+            Instruction::SetDelay(x) => self.reg_timer_delay = self.reg_v[x as usize],
+            Instruction::SetSound(x) => {
+                self.reg_timer_sound = self.reg_v[x as usize];
+                self.update_sound();
+            }
+            Instruction::AddI(x) => {
+                self.reg_i = u16::wrapping_add(self.reg_i, self.reg_v[x as usize] as u16);
+            }
+            Instruction::Hex(x) | Instruction::BigHex(x) => {
+                let value = self.reg_v[x as usize];
+                if value > 15 {
+                    return Err(ExecutionError::InvalidDigit {
+                        pc,
+                        opcode,
+                        register: x,
+                        value,
+                    });
+                }
+                self.reg_i = match instruction {
+                    Instruction::Hex(_) => SPRITE_START + value as usize * SPRITE_BYTE_WIDTH,
+                    _ => BIG_SPRITE_START + value as usize * BIG_SPRITE_BYTE_WIDTH,
+                } as u16;
+            }
+            Instruction::Bcd(x) => {
+                let value = self.reg_v[x as usize];
+                self.check_memory_range(pc, opcode, self.reg_i as usize, 3)?;
+                self.memory[self.reg_i as usize] = value / 100;
+                self.memory[self.reg_i as usize + 1] = (value / 10) % 10;
+                self.memory[self.reg_i as usize + 2] = value % 10;
+                self.writes.memory(self.reg_i as usize, 3);
+            }
+            Instruction::Pitch(x) => {
+                self.audio.pitch = self.reg_v[x as usize];
+                self.update_sound();
+            }
+            Instruction::Save(x) => {
+                let count = x as usize + 1;
+                let start = self.reg_i as usize;
+                self.check_memory_range(pc, opcode, start, count)?;
+                self.memory[start..start + count].copy_from_slice(&self.reg_v[..count]);
+                self.writes.memory(start, count);
+                self.increment_i_after_load_store(x);
+            }
+            Instruction::Load(x) => {
+                let count = x as usize + 1;
+                let start = self.reg_i as usize;
+                self.check_memory_range(pc, opcode, start, count)?;
+                self.reg_v[..count].copy_from_slice(&self.memory[start..start + count]);
+                self.increment_i_after_load_store(x);
+            }
+            Instruction::SaveFlags(x) => {
+                let count = x as usize + 1;
+                self.reg_flags[..count].copy_from_slice(&self.reg_v[..count]);
+            }
+            Instruction::LoadFlags(x) => {
+                let count = x as usize + 1;
+                self.reg_v[..count].copy_from_slice(&self.reg_flags[..count]);
+            }
+        }
+
+        self.reg_pc = next_pc;
+        Ok(StepOutcome::Executed)
+    }
+
+    /// Draws a sprite for `DXYN`, returning an outcome if drawing has to wait.
+    fn draw(
+        &mut self,
+        pc: u16,
+        opcode: u16,
+        x: u8,
+        y: u8,
+        n: u8,
+    ) -> Result<Option<StepOutcome>, ExecutionError> {
+        // N = 0 draws a 16x16 sprite.
+        let (sprite_width, sprite_height) = match n as usize {
+            0 => (16, 16),
+            row_count => (8, row_count),
+        };
+        let bytes_per_row = sprite_width / 8;
+        let sprite_size = sprite_height * bytes_per_row;
+        let vx_value = self.reg_v[x as usize] as usize;
+        let vy_value = self.reg_v[y as usize] as usize;
+
+        // Each selected plane is drawn with its own consecutive copy of the sprite data.
+        let plane_count = self.planes.count_ones() as usize;
+        self.check_memory_range(pc, opcode, self.reg_i as usize, sprite_size * plane_count)?;
+
+        if self.quirks.display_wait {
+            if !self.vblank_ready {
+                return Ok(Some(StepOutcome::WaitingForDisplay));
+            }
+            self.vblank_ready = false;
+        }
+
+        // The starting position always wraps, but the sprite itself may be clipped.
+        let display_width = self.display.width();
+        let display_height = self.display.height();
+        let start_x = vx_value % display_width;
+        let start_y = vy_value % display_height;
+
+        self.reg_v[15] = 0;
+        let mut sprite_start = self.reg_i as usize;
+        for plane in [1, 2] {
+            if self.planes & plane == 0 {
+                continue;
+            }
+            for row in 0..sprite_height {
+                if self.quirks.clip_sprites && start_y + row >= display_height {
+                    break;
+                }
+                let y = (start_y + row) % display_height;
+                for col in 0..sprite_width {
+                    if self.quirks.clip_sprites && start_x + col >= display_width {
+                        break;
+                    }
+                    let x = (start_x + col) % display_width;
+                    let sprite_byte = self.memory[sprite_start + row * bytes_per_row + col / 8];
+                    if sprite_byte & u8::pow(2, 7 - (col % 8) as u32) == 0 {
+                        continue;
+                    }
+
+                    // If a pixel was erased, set VF to 1.
+                    if self.display[y][x] & plane != 0 {
+                        self.reg_v[15] = 1;
+                    }
+
+                    // The pixel we should show will be the XOR'd value of the current display pixel and the bit in memory.
+                    self.display[y][x] ^= plane;
+                }
+            }
+            sprite_start += sprite_size;
+        }
+
+        self.mark_display_changed();
+        Ok(None)
+    }
+
+    pub fn should_play_sound(&self) -> bool {
+        self.reg_timer_sound > 1
+    }
+
+    /// The XO-CHIP audio pattern and pitch to play while `should_play_sound` is true.
+    pub fn audio(&self) -> &AudioState {
+        &self.audio
+    }
+
+    pub fn pc(&self) -> u16 {
+        self.reg_pc
+    }
+
+    pub fn i(&self) -> u16 {
+        self.reg_i
+    }
+
+    /// The general purpose registers V0-VF.
+    pub fn registers(&self) -> &[u8; 16] {
+        &self.reg_v
+    }
+
+    pub fn delay_timer(&self) -> u8 {
+        self.reg_timer_delay
+    }
+
+    pub fn sound_timer(&self) -> u8 {
+        self.reg_timer_sound
+    }
+
+    /// The addresses of the subroutine calls in progress, innermost last.
+    pub fn stack(&self) -> &[u16] {
+        &self.stack[..self.reg_sp as usize]
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    pub fn watchpoints(&self) -> &Watchpoints {
+        &self.watchpoints
+    }
+
+    pub fn watchpoints_mut(&mut self) -> &mut Watchpoints {
+        &mut self.watchpoints
+    }
+
+    /// The size of a save state, following the layout of `save_state_into`.
+    pub const STATE_SIZE: usize = OVERHEAD_SIZE
+        + 2 // PC
+        + 1 // SP
+        + 2 // I
+        + 2 // Timers
+        + 16 // V registers
+        + 16 // Flag registers
+        + STACK_SIZE * 2
+        + MEMORY_SIZE
+        + Display::STATE_SIZE
+        + 1 // Vblank
+        + 2 // Frame cycles
+        + 1 // Pressed key
+        + 1 // Planes
+        + PATTERN_SIZE
+        + 1 // Pitch
+        + 6 // Quirks
+        + R::STATE_SIZE;
+
+    /// Where memory starts in a save state.
+    #[cfg(feature = "std")]
+    pub(crate) const MEMORY_STATE_OFFSET: usize = crate::save_state::HEADER_SIZE
+        + 2 // PC
+        + 1 // SP
+        + 2 // I
+        + 2 // Timers
+        + 16 // V registers
+        + 16 // Flag registers
+        + STACK_SIZE * 2;
+
+    /// Where the display starts in a save state, right after memory.
+    #[cfg(feature = "std")]
+    pub(crate) const DISPLAY_STATE_OFFSET: usize = Self::MEMORY_STATE_OFFSET + MEMORY_SIZE;
+
+    /// Serializes the machine into a new buffer. See `save_state_into`.
+    #[cfg(feature = "std")]
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut state = vec![0; Self::STATE_SIZE];
+        self.save_state_into(&mut state)
+            .expect("To fit the save state.");
+        state
+    }
+
+    /// Serializes the machine: registers, stack, memory, display,
+    /// timers, audio, quirks and the RNG. Watchpoints and the cycles per
+    /// frame are not included. `buffer` needs room for `STATE_SIZE` bytes,
+    /// and the size written is returned.
+    pub fn save_state_into(&self, buffer: &mut [u8]) -> Result<usize, StateError> {
+        let mut writer = StateWriter::new(buffer);
+        self.write_state(&mut writer, &Writes::ALL);
+        writer.finish()
+    }
+
+    /// Writes the fields of a save state, skipping over the memory outside
+    /// `writes.memory` and, unless `writes.display` is set, the display. The
+    /// bytes skipped are left as they were in the writer's buffer.
+    pub(crate) fn write_state(&self, writer: &mut StateWriter, writes: &Writes) {
+        writer.u16(self.reg_pc);
+        writer.u8(self.reg_sp);
+        writer.u16(self.reg_i);
+        writer.u8(self.reg_timer_delay);
+        writer.u8(self.reg_timer_sound);
+        writer.bytes(&self.reg_v);
+        writer.bytes(&self.reg_flags);
+        for address in self.stack {
+            writer.u16(address);
+        }
+        writer.skip(writes.memory.start);
+        writer.bytes(&self.memory[writes.memory.clone()]);
+        writer.skip(MEMORY_SIZE - writes.memory.end);
+        if writes.display {
+            self.display.write_state(writer);
+        } else {
+            writer.skip(Display::STATE_SIZE);
+        }
+        writer.bool(self.vblank_ready);
+        writer.u16(self.frame_cycles);
+        writer.u8(self.pressed_key.unwrap_or(NO_KEY));
+        writer.u8(self.planes);
+        writer.bytes(&self.audio.pattern);
+        writer.u8(self.audio.pitch);
+        writer.bool(self.quirks.shift_uses_vy);
+        writer.u8(match self.quirks.load_store_increment {
+            LoadStoreIncrement::None => 0,
+            LoadStoreIncrement::PastLast => 1,
+            LoadStoreIncrement::ToLast => 2,
+        });
+        for quirk in [
+            self.quirks.jump_uses_vx,
+            self.quirks.clip_sprites,
+            self.quirks.vf_reset,
+            self.quirks.display_wait,
+        ] {
+            writer.bool(quirk);
+        }
+        self.rng.write_state(writer);
+    }
+
+    /// Takes what has changed since the last call, for recording history.
+    #[cfg(feature = "std")]
+    pub(crate) fn take_writes(&mut self) -> Writes {
+        core::mem::replace(&mut self.writes, Writes::NONE)
+    }
+
+    /// Restores a state produced by `save_state` or `save_state_into`. The machine is left
+    /// untouched if the state is rejected.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let mut reader = StateReader::new(data)?;
+        let reg_pc = reader.u16()?;
+        let reg_sp = reader.u8()?;
+        let reg_i = reader.u16()?;
+        let reg_timer_delay = reader.u8()?;
+        let reg_timer_sound = reader.u8()?;
+        let reg_v = reader.array()?;
+        let reg_flags = reader.array()?;
+        let mut stack = [0; STACK_SIZE];
+        for address in stack.iter_mut() {
+            *address = reader.u16()?;
+        }
+        let memory = reader.array::<MEMORY_SIZE>()?;
+        let display = Display::read_state(&mut reader)?;
+        let vblank_ready = reader.bool()?;
+        let frame_cycles = reader.u16()?;
+        let pressed_key = reader.u8()?;
+        let planes = reader.u8()?;
+        let audio = AudioState {
+            pattern: reader.array()?,
+            pitch: reader.u8()?,
+        };
+        let quirks = Quirks {
+            shift_uses_vy: reader.bool()?,
+            load_store_increment: match reader.u8()? {
+                0 => LoadStoreIncrement::None,
+                1 => LoadStoreIncrement::PastLast,
+                2 => LoadStoreIncrement::ToLast,
+                _ => return Err(StateError::InvalidData),
+            },
+            jump_uses_vx: reader.bool()?,
+            clip_sprites: reader.bool()?,
+            vf_reset: reader.bool()?,
+            display_wait: reader.bool()?,
+        };
+        let rng = R::read_state(&mut reader)?;
+        reader.finish()?;
+
+        if reg_sp as usize > STACK_SIZE
+            || planes > ALL_PLANES
+            || (pressed_key > 0xF && pressed_key != NO_KEY)
+        {
+            return Err(StateError::InvalidData);
+        }
+
+        self.reg_pc = reg_pc;
+        self.reg_sp = reg_sp;
+        self.reg_i = reg_i;
+        self.reg_timer_delay = reg_timer_delay;
+        self.reg_timer_sound = reg_timer_sound;
+        self.reg_v = reg_v;
+        self.reg_flags = reg_flags;
+        self.stack = stack;
+        self.memory = memory;
+        self.display = display;
+        self.vblank_ready = vblank_ready;
+        self.frame_cycles = frame_cycles;
+        self.pressed_key = (pressed_key != NO_KEY).then_some(pressed_key);
+        self.planes = planes;
+        self.audio = audio;
+        self.quirks = quirks;
+        self.rng = rng;
+
+        self.mark_display_changed();
+        self.writes = Writes::ALL;
+        self.update_sound();
+        Ok(())
+    }
+
+    pub fn host(&self) -> &H {
+        &self.host
+    }
+
+    pub fn host_mut(&mut self) -> &mut H {
+        &mut self.host
+    }
+
+    pub fn display(&self) -> &Display {
+        &self.display
+    }
+
+    /// Whether the display has changed since `clear_display_dirty` was last
+    /// called. Frontends can use this to only redraw when needed.
+    pub fn is_display_dirty(&self) -> bool {
+        self.display_dirty
+    }
+
+    pub fn clear_display_dirty(&mut self) {
+        self.display_dirty = false;
+    }
+
+    /// The memory `instruction` will read or write if it executes.
+    fn memory_access(&self, instruction: Instruction) -> Option<Access> {
+        let start = self.reg_i;
+        match instruction {
+            Instruction::Save(x) => Some(Access::Write {
+                start,
+                len: x as usize + 1,
+            }),
+            Instruction::SaveRange(x, y) => Some(Access::Write {
+                start,
+                len: u8::abs_diff(x, y) as usize + 1,
+            }),
+            Instruction::Bcd(_) => Some(Access::Write { start, len: 3 }),
+            Instruction::Load(x) => Some(Access::Read {
+                start,
+                len: x as usize + 1,
+            }),
+            Instruction::LoadRange(x, y) => Some(Access::Read {
+                start,
+                len: u8::abs_diff(x, y) as usize + 1,
+            }),
+            Instruction::Audio => Some(Access::Read {
+                start,
+                len: PATTERN_SIZE,
+            }),
+            Instruction::Draw(_, _, n) => {
+                // A 16x16 sprite is 32 bytes, and each selected plane has its own copy.
+                let sprite_size = if n == 0 { 32 } else { n as usize };
+                Some(Access::Read {
+                    start,
+                    len: sprite_size * self.planes.count_ones() as usize,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn register_snapshot(&self) -> RegisterSnapshot {
+        RegisterSnapshot {
+            v: self.reg_v,
+            i: self.reg_i,
+        }
+    }
+
+    /// Returns the address after the instruction at `address`, which is 4 bytes long for `F000 NNNN`.
+    fn skip_instruction_at(&self, address: u16) -> u16 {
+        let size = match get_opcode(&self.memory, address).map(Instruction::decode) {
+            Some(Ok(instruction)) => instruction.size(),
+            _ => 2,
+        };
+        u16::wrapping_add(address, size)
+    }
+
+    fn check_memory_range(
+        &self,
+        pc: u16,
+        opcode: u16,
+        start: usize,
+        length: usize,
+    ) -> Result<(), ExecutionError> {
+        if start + length > self.memory.len() {
+            return Err(ExecutionError::MemoryOutOfBounds {
+                pc,
+                opcode,
+                address: usize::max(start, self.memory.len()),
+            });
+        }
+        Ok(())
+    }
+
+    fn increment_i_after_load_store(&mut self, x: u8) {
+        let increment = match self.quirks.load_store_increment {
+            LoadStoreIncrement::None => return,
+            LoadStoreIncrement::ToLast => x as u16,
+            LoadStoreIncrement::PastLast => x as u16 + 1,
+        };
+        self.reg_i = u16::wrapping_add(self.reg_i, increment);
+    }
+
+    fn is_key_pressed(&self, pc: u16, opcode: u16, index: usize) -> Result<bool, ExecutionError> {
+        let value = self.reg_v[index];
+        if value > 15 {
+            return Err(ExecutionError::InvalidKey {
+                pc,
+                opcode,
+                register: index as u8,
+                value,
+            });
+        }
+        Ok(self.host.is_key_pressed(value))
+    }
+}
+
+fn get_opcode(memory: &[u8], pc: u16) -> Option<u16> {
+    // Encoding is in Big Endian.
+    let big = *memory.get(pc as usize)? as u16;
+    let little = *memory.get(pc as usize + 1)? as u16;
+
+    Some(big << 8 | little)
+}
+
+fn initialize_memory() -> [u8; MEMORY_SIZE] {
+    let mut memory = [0; MEMORY_SIZE];
+
+    // Set sprite data.
+    for (index, data) in ALL_SPRITE_DATA.into_iter().enumerate() {
+        memory[SPRITE_START + index] = data;
+    }
+    for (index, data) in ALL_BIG_SPRITE_DATA.into_iter().enumerate() {
+        memory[BIG_SPRITE_START + index] = data;
+    }
+
+    memory
+}
+
+const ALL_SPRITE_DATA: [u8; SPRITE_BYTE_WIDTH * SPRITE_COUNT] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // Zero
+    0x20, 0x60, 0x20, 0x20, 0x70, // One
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // Two
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // Three
+    0x90, 0x90, 0xF0, 0x10, 0x10, // Four
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // Five
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // Six
+    0xF0, 0x10, 0x20, 0x40, 0x40, // Seven
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // Eight
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // Nine
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80, // F
+];
+const ALL_BIG_SPRITE_DATA: [u8; BIG_SPRITE_BYTE_WIDTH * SPRITE_COUNT] = [
+    0xFF, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, // Zero
+    0x18, 0x78, 0x78, 0x18, 0x18, 0x18, 0x18, 0x18, 0xFF, 0xFF, // One
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // Two
+    0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // Three
+    0xC3, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0x03, 0x03, // Four
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // Five
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // Six
+    0xFF, 0xFF, 0x03, 0x03, 0x06, 0x0C, 0x18, 0x18, 0x18, 0x18, // Seven
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, // Eight
+    0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0x03, 0x03, 0xFF, 0xFF, // Nine
+    0x7E, 0xFF, 0xC3, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, // A
+    0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, // B
+    0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+    0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+    0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0, // F
+];
+const MEMORY_SIZE: usize = 0x10000;
+pub const PROG_START: usize = 0x200;
+pub const PROG_END: usize = MEMORY_SIZE;
+const SPRITE_COUNT: usize = 16;
+const SPRITE_START: usize = 0;
+const SPRITE_BYTE_WIDTH: usize = 5;
+const BIG_SPRITE_START: usize = SPRITE_START + SPRITE_BYTE_WIDTH * SPRITE_COUNT;
+const BIG_SPRITE_BYTE_WIDTH: usize = 10;
+const STACK_SIZE: usize = 16;
+/// Stands for no key in save states.
+const NO_KEY: u8 = 0xFF;
+/// The rate at which the timers count down, in Hz.
+pub const TIMER_CLOCK: u32 = 60;
+/// About 500 instructions per second.
+pub const DEFAULT_CYCLES_PER_FRAME: u16 = 8;
+
+#[cfg(test)]
+#[path = "./emulator_test.rs"]
+mod emulator_test;
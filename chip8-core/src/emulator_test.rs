@@ -0,0 +1,1475 @@
+use super::{get_opcode, Chip8, ExecutionError, StepOutcome, PROG_END, PROG_START};
+use crate::{
+    audio::AudioState,
+    display::{Display, HIRES_HEIGHT, HIRES_WIDTH, LORES_HEIGHT, LORES_WIDTH},
+    emulator::{BIG_SPRITE_START, SPRITE_START},
+    host::Host,
+    quirks::Quirks,
+    random::{RandomSource, SplitMix64},
+    save_state::StateError,
+    watchpoint::{Action, Condition, Event, Predicate, Register, Watchpoint, WatchpointHit},
+};
+
+#[test]
+fn can_get_opcode() {
+    let mut chip8 = get_emulator();
+    chip8.memory[chip8.reg_pc as usize] = 0xF8;
+    chip8.memory[(chip8.reg_pc + 1) as usize] = 0x32;
+    let opcode = get_opcode(&chip8.memory, chip8.reg_pc).unwrap();
+    assert_eq!(opcode, 0xF832);
+}
+
+#[test]
+fn loads_rom_data() {
+    let mut chip8 = get_emulator();
+    let data = vec![1; PROG_END - PROG_START];
+    chip8.load(&data).unwrap();
+    assert_eq!(chip8.memory[PROG_START - 1], 0);
+    assert_eq!(chip8.memory[PROG_START], 1);
+    assert_eq!(chip8.memory[PROG_END - 1], 1);
+}
+
+#[test]
+#[should_panic(expected = "ROM data is too large for memory.")]
+fn prevents_rom_overflow() {
+    let mut chip8 = get_emulator();
+    let data = vec![1; PROG_END - PROG_START + 1];
+    chip8.load(&data).unwrap();
+}
+
+#[test]
+fn clear() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x00;
+    chip8.memory[PROG_START + 1] = 0xE0;
+    chip8.display[0][0] = 1;
+    chip8.display[LORES_HEIGHT - 1][LORES_WIDTH - 1] = 1;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc, 0x202);
+    assert_eq!(chip8.display, Display::new());
+}
+
+#[test]
+fn calls_subroutine() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x22;
+    chip8.memory[PROG_START + 1] = 0x38;
+    chip8.reg_sp = 15;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc, 0x238);
+    assert_eq!(chip8.reg_sp, 16);
+    assert_eq!(chip8.stack[15] as usize, PROG_START);
+}
+
+#[test]
+fn exposes_state_for_inspection() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x22;
+    chip8.memory[PROG_START + 1] = 0x38;
+    chip8.reg_i = 0x300;
+    chip8.reg_v[3] = 7;
+    chip8.reg_timer_delay = 5;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.pc(), 0x238);
+    assert_eq!(chip8.i(), 0x300);
+    assert_eq!(chip8.registers()[3], 7);
+    assert_eq!(chip8.delay_timer(), 5);
+    assert_eq!(chip8.sound_timer(), 0);
+    assert_eq!(chip8.stack(), [PROG_START as u16]);
+    assert_eq!(chip8.memory()[PROG_START], 0x22);
+}
+
+#[test]
+fn prevents_stack_overflow() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x22;
+    chip8.memory[PROG_START + 1] = 0x38;
+    chip8.reg_sp = 16;
+    assert_eq!(
+        chip8.cycle(),
+        Err(ExecutionError::StackOverflow {
+            pc: 0x200,
+            opcode: 0x2238
+        })
+    );
+    assert_eq!(chip8.reg_pc as usize, PROG_START);
+}
+
+#[test]
+fn returns_from_subroutine() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x00;
+    chip8.memory[PROG_START + 1] = 0xEE;
+    chip8.stack[1] = 0x2F8;
+    chip8.reg_sp = 2;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc, 0x2FA);
+    assert_eq!(chip8.reg_sp, 1);
+}
+
+#[test]
+fn prevents_stack_underflow() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x00;
+    chip8.memory[PROG_START + 1] = 0xEE;
+    chip8.reg_sp = 0;
+    assert_eq!(
+        chip8.cycle(),
+        Err(ExecutionError::StackUnderflow {
+            pc: 0x200,
+            opcode: 0x00EE
+        })
+    );
+    assert_eq!(chip8.reg_sp, 0);
+}
+
+#[test]
+fn jump() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x1A;
+    chip8.memory[PROG_START + 1] = 0xF8;
+    chip8.memory[0x0AF8] = 1;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc, 0x0AF8);
+    assert_eq!(chip8.memory[chip8.reg_pc as usize], 1);
+}
+
+#[test]
+fn set_register_to_const() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x63;
+    chip8.memory[PROG_START + 1] = 0x64;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc, 0x202);
+    assert_eq!(chip8.reg_v[3], 0x64);
+}
+
+#[test]
+fn set_i_to_const() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xA3;
+    chip8.memory[PROG_START + 1] = 0x64;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc, 0x202);
+    assert_eq!(chip8.reg_i, 0x364);
+}
+
+#[test]
+fn enter_and_exit_subroutine() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x23;
+    chip8.memory[PROG_START + 1] = 0x64;
+    chip8.memory[0x364] = 0x00;
+    chip8.memory[0x365] = 0xEE;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc, 0x364);
+    assert_eq!(chip8.reg_sp, 1);
+    assert_eq!(chip8.stack[0], 0x200);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc, 0x202);
+    assert_eq!(chip8.reg_sp, 0);
+}
+
+#[test]
+fn check_key_pressed() {
+    let mut chip8 = get_emulator_with_host();
+    chip8.memory[PROG_START] = 0xE4;
+    chip8.memory[PROG_START + 1] = 0x9E;
+    chip8.memory[PROG_START + 2] = 0xE3;
+    chip8.memory[PROG_START + 3] = 0xA1;
+    chip8.memory[PROG_START + 4] = 0xE3;
+    chip8.memory[PROG_START + 5] = 0x9E;
+    chip8.memory[PROG_START + 8] = 0xE4;
+    chip8.memory[PROG_START + 9] = 0xA1;
+    chip8.reg_v[3] = 4;
+    chip8.host.keys[4] = true;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 8);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 12);
+}
+
+#[test]
+fn set_delay_timer() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x15;
+    chip8.reg_v[4] = 60;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_timer_delay, 60);
+}
+
+#[test]
+fn set_sound_timer() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x18;
+    chip8.reg_v[4] = 60;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_timer_sound, 60);
+}
+
+#[test]
+fn v_not_equals_const() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x34;
+    chip8.memory[PROG_START + 1] = 0x18;
+    chip8.memory[PROG_START + 4] = 0x34;
+    chip8.memory[PROG_START + 5] = 0x17;
+    chip8.reg_v[4] = 0x18;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 6);
+}
+
+#[test]
+fn v_equals_const() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x44;
+    chip8.memory[PROG_START + 1] = 0x18;
+    chip8.memory[PROG_START + 2] = 0x44;
+    chip8.memory[PROG_START + 3] = 0x17;
+    chip8.reg_v[4] = 0x18;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 6);
+}
+
+#[test]
+fn vx_equals_vy() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x54;
+    chip8.memory[PROG_START + 1] = 0x30;
+    chip8.memory[PROG_START + 4] = 0x54;
+    chip8.memory[PROG_START + 5] = 0x20;
+    chip8.reg_v[2] = 0x04;
+    chip8.reg_v[3] = 0x18;
+    chip8.reg_v[4] = 0x18;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 6);
+}
+
+#[test]
+fn vx_not_equals_vy() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x94;
+    chip8.memory[PROG_START + 1] = 0x30;
+    chip8.memory[PROG_START + 2] = 0x94;
+    chip8.memory[PROG_START + 3] = 0x20;
+    chip8.reg_v[2] = 0x04;
+    chip8.reg_v[3] = 0x18;
+    chip8.reg_v[4] = 0x18;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 6);
+}
+
+#[test]
+fn generate_random() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xC4;
+    chip8.memory[PROG_START + 1] = 0xFF;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], SplitMix64::default().next_byte());
+}
+
+#[test]
+fn generate_random_is_masked() {
+    let mut chip8 = get_emulator();
+    for _ in 0..32 {
+        chip8.memory[PROG_START] = 0xC4;
+        chip8.memory[PROG_START + 1] = 0x0F;
+        chip8.reg_pc = PROG_START as u16;
+        chip8.cycle().unwrap();
+        assert_eq!(chip8.reg_v[4] & 0xF0, 0);
+    }
+}
+
+#[test]
+fn generate_random_from_seed() {
+    let random_bytes = |seed| {
+        let mut chip8 = Chip8::with_rng(Quirks::XO_CHIP, SplitMix64::new(seed));
+        chip8.load(&[0xC0, 0xFF, 0xC1, 0xFF, 0xC2, 0xFF]).unwrap();
+        for _ in 0..3 {
+            chip8.cycle().unwrap();
+        }
+        chip8.reg_v[..3].to_vec()
+    };
+    assert_eq!(random_bytes(7), random_bytes(7));
+    assert_ne!(random_bytes(7), random_bytes(8));
+}
+
+#[test]
+fn bcd() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x33;
+    chip8.reg_v[4] = 245;
+    chip8.reg_i = 0x2F5;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_i, 0x2F5);
+    assert_eq!(chip8.reg_v[4], 245);
+    assert_eq!(chip8.memory[0x2F5], 2);
+    assert_eq!(chip8.memory[0x2F6], 4);
+    assert_eq!(chip8.memory[0x2F7], 5);
+}
+
+#[test]
+fn save_vx() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF3;
+    chip8.memory[PROG_START + 1] = 0x55;
+    chip8.reg_v[0] = 245;
+    chip8.reg_v[1] = 0;
+    chip8.reg_v[2] = 10;
+    chip8.reg_v[3] = 42;
+    chip8.reg_v[4] = 19;
+    chip8.reg_i = 0x2F0;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_i, 0x2F4);
+    assert_eq!(chip8.reg_v[0], 245);
+    assert_eq!(chip8.memory[0x2F0], 245);
+    assert_eq!(chip8.memory[0x2F1], 0);
+    assert_eq!(chip8.memory[0x2F2], 10);
+    assert_eq!(chip8.memory[0x2F3], 42);
+    assert_ne!(chip8.memory[0x2F4], 19);
+}
+
+#[test]
+fn load_vx() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF3;
+    chip8.memory[PROG_START + 1] = 0x65;
+    chip8.reg_i = 0x2F0;
+    chip8.memory[chip8.reg_i as usize] = 245;
+    chip8.memory[chip8.reg_i as usize + 1] = 0;
+    chip8.memory[chip8.reg_i as usize + 2] = 10;
+    chip8.memory[chip8.reg_i as usize + 3] = 42;
+    chip8.memory[chip8.reg_i as usize + 4] = 19;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_i, 0x2F4);
+    assert_eq!(chip8.memory[0x2F0], 245);
+    assert_eq!(chip8.reg_v[0], 245);
+    assert_eq!(chip8.reg_v[1], 0);
+    assert_eq!(chip8.reg_v[2], 10);
+    assert_eq!(chip8.reg_v[3], 42);
+    assert_ne!(chip8.reg_v[4], 19);
+}
+
+#[test]
+fn add_vx_to_i() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x1E;
+    chip8.reg_i = 0x2F0;
+    chip8.reg_v[4] = 3;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 3);
+    assert_eq!(chip8.reg_i, 0x2F3);
+}
+
+#[test]
+fn add_const_to_vx() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x74;
+    chip8.memory[PROG_START + 1] = 0x05;
+    chip8.reg_v[4] = 3;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 8);
+}
+
+#[test]
+fn set_i_to_sprite_for_vx() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x29;
+    chip8.reg_v[4] = 12;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 12);
+    assert_eq!(chip8.reg_i, (SPRITE_START + 60) as u16);
+    assert_eq!(chip8.memory[chip8.reg_i as usize], 0xF0);
+    assert_eq!(chip8.memory[chip8.reg_i as usize + 1], 0x80);
+    assert_eq!(chip8.memory[chip8.reg_i as usize + 2], 0x80);
+    assert_eq!(chip8.memory[chip8.reg_i as usize + 3], 0x80);
+    assert_eq!(chip8.memory[chip8.reg_i as usize + 4], 0xF0);
+}
+
+#[test]
+fn set_i_to_sprite_for_invalid_vx() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x29;
+    chip8.reg_v[4] = 17;
+    assert_eq!(
+        chip8.cycle(),
+        Err(ExecutionError::InvalidDigit {
+            pc: 0x200,
+            opcode: 0xF429,
+            register: 4,
+            value: 17
+        })
+    );
+}
+
+#[test]
+fn rejects_unknown_opcode() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0xFF;
+    assert_eq!(
+        chip8.cycle(),
+        Err(ExecutionError::UnknownOpcode {
+            pc: 0x200,
+            opcode: 0xF4FF
+        })
+    );
+}
+
+#[test]
+fn rejects_invalid_key() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xE4;
+    chip8.memory[PROG_START + 1] = 0x9E;
+    chip8.reg_v[4] = 16;
+    assert_eq!(
+        chip8.cycle(),
+        Err(ExecutionError::InvalidKey {
+            pc: 0x200,
+            opcode: 0xE49E,
+            register: 4,
+            value: 16
+        })
+    );
+}
+
+#[test]
+fn prevents_memory_overflow() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF3;
+    chip8.memory[PROG_START + 1] = 0x55;
+    chip8.reg_i = 0xFFFE;
+    assert_eq!(
+        chip8.cycle(),
+        Err(ExecutionError::MemoryOutOfBounds {
+            pc: 0x200,
+            opcode: 0xF355,
+            address: 0x10000
+        })
+    );
+    assert_eq!(chip8.reg_i, 0xFFFE);
+}
+
+#[test]
+fn prevents_pc_overflow() {
+    let mut chip8 = get_emulator();
+    chip8.reg_pc = 0xFFFF;
+    assert_eq!(
+        chip8.cycle(),
+        Err(ExecutionError::PcOutOfBounds { pc: 0xFFFF })
+    );
+}
+
+#[test]
+fn set_vx_to_vy() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x50;
+    chip8.reg_v[5] = 17;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 17);
+}
+
+#[test]
+fn set_vx_to_or_vy() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x51;
+    chip8.reg_v[4] = 0b1010;
+    chip8.reg_v[5] = 0b0100;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 0b1110);
+}
+
+#[test]
+fn set_vx_to_and_vy() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x52;
+    chip8.reg_v[4] = 0b1010;
+    chip8.reg_v[5] = 0b1100;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 0b1000);
+}
+
+#[test]
+fn set_vx_to_xor_vy() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x53;
+    chip8.reg_v[4] = 0b1010;
+    chip8.reg_v[5] = 0b1100;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 0b0110);
+}
+
+#[test]
+fn add_vx_vy() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x54;
+    chip8.reg_v[4] = 1;
+    chip8.reg_v[5] = 2;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 3);
+    assert_eq!(chip8.reg_v[15], 0)
+}
+
+#[test]
+fn add_vx_vy_carry() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x54;
+    chip8.reg_v[4] = 255;
+    chip8.reg_v[5] = 100;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 99);
+    assert_eq!(chip8.reg_v[15], 1)
+}
+
+#[test]
+fn sub_vx_vy() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x55;
+    chip8.reg_v[4] = 4;
+    chip8.reg_v[5] = 2;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 2);
+    assert_eq!(chip8.reg_v[15], 1)
+}
+
+#[test]
+fn sub_vx_vy_borrow() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x55;
+    chip8.reg_v[4] = 100;
+    chip8.reg_v[5] = 255;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 101);
+    assert_eq!(chip8.reg_v[15], 0)
+}
+
+#[test]
+fn shift_right() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x56;
+    chip8.reg_v[5] = 0b1001_1111;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 0b0100_1111);
+    assert_eq!(chip8.reg_v[5], 0b1001_1111);
+    assert_eq!(chip8.reg_v[15], 1)
+}
+
+#[test]
+fn shift_left() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x5E;
+    chip8.reg_v[5] = 0b1001_1111;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 0b0011_1110);
+    assert_eq!(chip8.reg_v[5], 0b1001_1111);
+    assert_eq!(chip8.reg_v[15], 1)
+}
+
+#[test]
+fn shift_left_sets_vf_to_shifted_out_bit() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::SUPER_CHIP);
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x4E;
+    chip8.memory[PROG_START + 2] = 0x84;
+    chip8.memory[PROG_START + 3] = 0x4E;
+    chip8.reg_v[4] = 0b0100_0000;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_v[4], 0b1000_0000);
+    assert_eq!(chip8.reg_v[15], 0);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_v[4], 0);
+    assert_eq!(chip8.reg_v[15], 1);
+}
+
+#[test]
+fn sub_vy_vx() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x57;
+    chip8.reg_v[4] = 2;
+    chip8.reg_v[5] = 4;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 2);
+    assert_eq!(chip8.reg_v[15], 1)
+}
+
+#[test]
+fn sub_vy_vx_borrow() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x57;
+    chip8.reg_v[4] = 255;
+    chip8.reg_v[5] = 100;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 101);
+    assert_eq!(chip8.reg_v[15], 0)
+}
+
+#[test]
+fn sub_of_equal_values_does_not_borrow() {
+    let mut chip8 = get_emulator();
+    chip8.load(&[0x84, 0x55, 0x86, 0x77]).unwrap();
+    chip8.reg_v[4] = 42;
+    chip8.reg_v[5] = 42;
+    chip8.reg_v[6] = 7;
+    chip8.reg_v[7] = 7;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_v[4], 0);
+    assert_eq!(chip8.reg_v[15], 1);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_v[6], 0);
+    assert_eq!(chip8.reg_v[15], 1);
+}
+
+#[test]
+fn jump0() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xBF;
+    chip8.memory[PROG_START + 1] = 0x32;
+    chip8.reg_v[0] = 5;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, 0x0F37);
+}
+
+#[test]
+fn set_vx_delay() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x07;
+    chip8.reg_timer_delay = 8;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 8)
+}
+
+#[test]
+fn wait_for_key() {
+    let mut chip8 = get_emulator_with_host();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x0A;
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::WaitingForKey));
+    assert_eq!(chip8.reg_pc as usize, PROG_START);
+    assert_eq!(chip8.host.waits, 1);
+    chip8.host.keys[10] = true;
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::WaitingForKey));
+    assert_eq!(chip8.reg_pc as usize, PROG_START);
+    assert_eq!(chip8.host.waits, 2);
+    chip8.host.keys[10] = false;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 10)
+}
+
+#[test]
+fn wait_for_key_ignores_other_releases() {
+    let mut chip8 = get_emulator_with_host();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x0A;
+    chip8.host.keys[3] = true;
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::WaitingForKey));
+    chip8.host.keys[7] = true;
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::WaitingForKey));
+    chip8.host.keys[7] = false;
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::WaitingForKey));
+    chip8.host.keys[3] = false;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[4], 3)
+}
+
+#[test]
+fn draw_sprite() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xD4;
+    chip8.memory[PROG_START + 1] = 0x55;
+    chip8.memory[PROG_START + 2] = 0xD4;
+    chip8.memory[PROG_START + 3] = 0x55;
+    chip8.reg_v[4] = 5;
+    chip8.reg_v[5] = 10;
+    chip8.reg_i = SPRITE_START as u16 + 5;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_v[15], 0);
+    chip8.reg_i = SPRITE_START as u16;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+    assert_eq!(chip8.reg_v[15], 1);
+    assert_eq!(chip8.display[10][4], 0);
+    assert_eq!(chip8.display[10][5], 1);
+    assert_eq!(chip8.display[10][6], 1);
+    assert_eq!(chip8.display[10][7], 0);
+    assert_eq!(chip8.display[10][8], 1);
+    assert_eq!(chip8.display[10][9], 0);
+    assert_eq!(chip8.display[11][4], 0);
+    assert_eq!(chip8.display[11][5], 1);
+    assert_eq!(chip8.display[11][6], 1);
+    assert_eq!(chip8.display[11][7], 1);
+    assert_eq!(chip8.display[11][8], 1);
+    assert_eq!(chip8.display[11][9], 0);
+    assert_eq!(chip8.display[12][4], 0);
+    assert_eq!(chip8.display[12][5], 1);
+    assert_eq!(chip8.display[12][6], 0);
+    assert_eq!(chip8.display[12][7], 1);
+    assert_eq!(chip8.display[12][8], 1);
+    assert_eq!(chip8.display[12][9], 0);
+    assert_eq!(chip8.display[13][4], 0);
+    assert_eq!(chip8.display[13][5], 1);
+    assert_eq!(chip8.display[13][6], 0);
+    assert_eq!(chip8.display[13][7], 1);
+    assert_eq!(chip8.display[13][8], 1);
+    assert_eq!(chip8.display[13][9], 0);
+    assert_eq!(chip8.display[14][4], 0);
+    assert_eq!(chip8.display[14][5], 1);
+    assert_eq!(chip8.display[14][6], 0);
+    assert_eq!(chip8.display[14][7], 0);
+    assert_eq!(chip8.display[14][8], 0);
+    assert_eq!(chip8.display[14][9], 0);
+}
+
+#[test]
+fn decrements_timers() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x15;
+    chip8.memory[PROG_START + 2] = 0xF4;
+    chip8.memory[PROG_START + 3] = 0x18;
+    chip8.memory[PROG_START + 4] = 0x12;
+    chip8.memory[PROG_START + 5] = 0x04;
+    chip8.reg_v[4] = 30;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_timer_delay, 30);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_timer_sound, 30);
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+    chip8.tick_60hz();
+    assert_eq!(chip8.reg_timer_delay, 29);
+    assert_eq!(chip8.reg_timer_sound, 29);
+}
+
+#[test]
+fn ticks_timers_after_cycles_per_frame() {
+    let mut chip8 = get_emulator();
+    chip8.set_cycles_per_frame(3);
+    chip8.memory[PROG_START] = 0x12;
+    chip8.memory[PROG_START + 1] = 0x00;
+    chip8.reg_timer_delay = 10;
+    chip8.reg_timer_sound = 1;
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_timer_delay, 10);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_timer_delay, 9);
+    assert_eq!(chip8.reg_timer_sound, 0);
+    for _ in 0..6 {
+        chip8.cycle().unwrap();
+    }
+    assert_eq!(chip8.reg_timer_delay, 7);
+    assert_eq!(chip8.reg_timer_sound, 0);
+}
+
+#[test]
+fn only_ticks_timers_manually_without_cycles_per_frame() {
+    let mut chip8 = get_emulator();
+    chip8.set_cycles_per_frame(0);
+    chip8.memory[PROG_START] = 0x12;
+    chip8.memory[PROG_START + 1] = 0x00;
+    chip8.reg_timer_delay = 10;
+    for _ in 0..100 {
+        chip8.cycle().unwrap();
+    }
+    assert_eq!(chip8.reg_timer_delay, 10);
+    chip8.tick_60hz();
+    assert_eq!(chip8.reg_timer_delay, 9);
+}
+
+#[test]
+fn shift_in_place() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::SUPER_CHIP);
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x56;
+    chip8.memory[PROG_START + 2] = 0x84;
+    chip8.memory[PROG_START + 3] = 0x5E;
+    chip8.reg_v[4] = 0b0000_0011;
+    chip8.reg_v[5] = 0b1001_1110;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_v[4], 0b0000_0001);
+    assert_eq!(chip8.reg_v[15], 1);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_v[4], 0b0000_0010);
+    assert_eq!(chip8.reg_v[15], 0);
+}
+
+#[test]
+fn jump_with_vx() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::SUPER_CHIP);
+    chip8.memory[PROG_START] = 0xB3;
+    chip8.memory[PROG_START + 1] = 0x32;
+    chip8.reg_v[0] = 5;
+    chip8.reg_v[3] = 7;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, 0x0339);
+}
+
+#[test]
+fn logic_resets_vf() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::COSMAC_VIP);
+    chip8.memory[PROG_START] = 0x84;
+    chip8.memory[PROG_START + 1] = 0x51;
+    chip8.reg_v[4] = 0b1010;
+    chip8.reg_v[5] = 0b0100;
+    chip8.reg_v[15] = 1;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_v[4], 0b1110);
+    assert_eq!(chip8.reg_v[15], 0);
+}
+
+#[test]
+fn save_and_load_without_incrementing_i() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::SUPER_CHIP);
+    chip8.memory[PROG_START] = 0xF1;
+    chip8.memory[PROG_START + 1] = 0x55;
+    chip8.memory[PROG_START + 2] = 0xF1;
+    chip8.memory[PROG_START + 3] = 0x65;
+    chip8.reg_v[0] = 3;
+    chip8.reg_v[1] = 4;
+    chip8.reg_i = 0x2F0;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_i, 0x2F0);
+    assert_eq!(chip8.memory[0x2F0], 3);
+    assert_eq!(chip8.memory[0x2F1], 4);
+    chip8.reg_v[0] = 0;
+    chip8.reg_v[1] = 0;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_i, 0x2F0);
+    assert_eq!(chip8.reg_v[0], 3);
+    assert_eq!(chip8.reg_v[1], 4);
+}
+
+#[test]
+fn save_and_load_incrementing_i_to_last_register() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::CHIP_48);
+    chip8.memory[PROG_START] = 0xF1;
+    chip8.memory[PROG_START + 1] = 0x55;
+    chip8.memory[PROG_START + 2] = 0xF1;
+    chip8.memory[PROG_START + 3] = 0x65;
+    chip8.reg_v[0] = 3;
+    chip8.reg_v[1] = 4;
+    chip8.reg_i = 0x2F0;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_i, 0x2F1);
+    assert_eq!(chip8.memory[0x2F1], 4);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_i, 0x2F2);
+    assert_eq!(chip8.reg_v[0], 4);
+    assert_eq!(chip8.reg_v[1], 0);
+
+    let mut restored = get_emulator();
+    restored.load_state(&chip8.save_state()).unwrap();
+    assert_eq!(restored.quirks, Quirks::CHIP_48);
+}
+
+#[test]
+fn draw_sprite_wraps() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::XO_CHIP);
+    chip8.memory[PROG_START] = 0xD4;
+    chip8.memory[PROG_START + 1] = 0x52;
+    chip8.reg_v[4] = 62;
+    chip8.reg_v[5] = 31;
+    chip8.reg_i = SPRITE_START as u16;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.display[31][62], 1);
+    assert_eq!(chip8.display[31][63], 1);
+    assert_eq!(chip8.display[31][0], 1);
+    assert_eq!(chip8.display[0][62], 1);
+    assert_eq!(chip8.display[0][63], 0);
+}
+
+#[test]
+fn draw_sprite_clips() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::COSMAC_VIP);
+    chip8.memory[PROG_START] = 0xD4;
+    chip8.memory[PROG_START + 1] = 0x52;
+    chip8.reg_v[4] = 62 + 64;
+    chip8.reg_v[5] = 31;
+    chip8.reg_i = SPRITE_START as u16;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.display[31][62], 1);
+    assert_eq!(chip8.display[31][63], 1);
+    assert_eq!(chip8.display[31][0], 0);
+    assert_eq!(chip8.display[0][62], 0);
+}
+
+#[test]
+fn draw_sprite_waits_for_display() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::COSMAC_VIP);
+    chip8.memory[PROG_START] = 0xD4;
+    chip8.memory[PROG_START + 1] = 0x51;
+    chip8.memory[PROG_START + 2] = 0xD4;
+    chip8.memory[PROG_START + 3] = 0x51;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::WaitingForDisplay));
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    chip8.tick_60hz();
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::Executed));
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+}
+
+#[test]
+fn switch_resolution() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x00;
+    chip8.memory[PROG_START + 1] = 0xFF;
+    chip8.memory[PROG_START + 2] = 0x00;
+    chip8.memory[PROG_START + 3] = 0xFE;
+    chip8.display[0][0] = 1;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.display.width(), HIRES_WIDTH);
+    assert_eq!(chip8.display.height(), HIRES_HEIGHT);
+    assert_eq!(chip8.display[0][0], 0);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+    assert_eq!(chip8.display.width(), LORES_WIDTH);
+    assert_eq!(chip8.display.height(), LORES_HEIGHT);
+}
+
+#[test]
+fn scroll() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x00;
+    chip8.memory[PROG_START + 1] = 0xC3;
+    chip8.memory[PROG_START + 2] = 0x00;
+    chip8.memory[PROG_START + 3] = 0xFB;
+    chip8.memory[PROG_START + 4] = 0x00;
+    chip8.memory[PROG_START + 5] = 0xFC;
+    chip8.display[0][1] = 1;
+    chip8.display[LORES_HEIGHT - 1][1] = 1;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.display[0][1], 0);
+    assert_eq!(chip8.display[3][1], 1);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.display[3][1], 0);
+    assert_eq!(chip8.display[3][5], 1);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.display[3][5], 0);
+    assert_eq!(chip8.display[3][1], 1);
+    assert_eq!(
+        chip8
+            .display
+            .rows()
+            .flatten()
+            .filter(|pixel| **pixel != 0)
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn draw_large_sprite() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x00;
+    chip8.memory[PROG_START + 1] = 0xFF;
+    chip8.memory[PROG_START + 2] = 0xD4;
+    chip8.memory[PROG_START + 3] = 0x50;
+    chip8.memory[0x300] = 0x80;
+    chip8.memory[0x301] = 0x01;
+    chip8.memory[0x31E] = 0x80;
+    chip8.memory[0x31F] = 0x01;
+    chip8.reg_v[4] = 100;
+    chip8.reg_v[5] = 40;
+    chip8.reg_i = 0x300;
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+    assert_eq!(chip8.display[40][100], 1);
+    assert_eq!(chip8.display[40][115], 1);
+    assert_eq!(chip8.display[55][100], 1);
+    assert_eq!(chip8.display[55][115], 1);
+    assert_eq!(
+        chip8
+            .display
+            .rows()
+            .flatten()
+            .filter(|pixel| **pixel != 0)
+            .count(),
+        4
+    );
+}
+
+#[test]
+fn set_i_to_big_sprite_for_vx() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF4;
+    chip8.memory[PROG_START + 1] = 0x30;
+    chip8.reg_v[4] = 2;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_i, (BIG_SPRITE_START + 20) as u16);
+    assert_eq!(chip8.memory[chip8.reg_i as usize], 0xFF);
+    assert_eq!(chip8.memory[chip8.reg_i as usize + 9], 0xFF);
+}
+
+#[test]
+fn save_and_load_flags() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF2;
+    chip8.memory[PROG_START + 1] = 0x75;
+    chip8.memory[PROG_START + 2] = 0xF2;
+    chip8.memory[PROG_START + 3] = 0x85;
+    chip8.reg_v[0] = 1;
+    chip8.reg_v[1] = 2;
+    chip8.reg_v[2] = 3;
+    chip8.reg_v[3] = 4;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_flags[..4], [1, 2, 3, 0]);
+    chip8.reg_v = [0; 16];
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_v[..4], [1, 2, 3, 0]);
+}
+
+#[test]
+fn exit() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x00;
+    chip8.memory[PROG_START + 1] = 0xFD;
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::Exited));
+    assert_eq!(chip8.reg_pc as usize, PROG_START);
+}
+
+#[test]
+fn set_i_to_long_const() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF0;
+    chip8.memory[PROG_START + 1] = 0x00;
+    chip8.memory[PROG_START + 2] = 0xE1;
+    chip8.memory[PROG_START + 3] = 0x23;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+    assert_eq!(chip8.reg_i, 0xE123);
+}
+
+#[test]
+fn skips_long_instruction() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x34;
+    chip8.memory[PROG_START + 1] = 0x00;
+    chip8.memory[PROG_START + 2] = 0xF0;
+    chip8.memory[PROG_START + 3] = 0x00;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 6);
+}
+
+#[test]
+fn save_and_load_register_range() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x52;
+    chip8.memory[PROG_START + 1] = 0x42;
+    chip8.memory[PROG_START + 2] = 0x56;
+    chip8.memory[PROG_START + 3] = 0x43;
+    chip8.reg_v[2] = 7;
+    chip8.reg_v[3] = 8;
+    chip8.reg_v[4] = 9;
+    chip8.reg_i = 0x2F0;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_i, 0x2F0);
+    assert_eq!(chip8.memory[0x2F0..0x2F4], [7, 8, 9, 0]);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_i, 0x2F0);
+    assert_eq!(chip8.reg_v[3..7], [8, 9, 8, 7]);
+}
+
+#[test]
+fn draw_sprite_on_planes() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF3;
+    chip8.memory[PROG_START + 1] = 0x01;
+    chip8.memory[PROG_START + 2] = 0xD0;
+    chip8.memory[PROG_START + 3] = 0x01;
+    chip8.memory[PROG_START + 4] = 0xF2;
+    chip8.memory[PROG_START + 5] = 0x01;
+    chip8.memory[PROG_START + 6] = 0x00;
+    chip8.memory[PROG_START + 7] = 0xE0;
+    chip8.memory[0x300] = 0b1100_0000;
+    chip8.memory[0x301] = 0b1010_0000;
+    chip8.reg_i = 0x300;
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.display[0][0], 0b11);
+    assert_eq!(chip8.display[0][1], 0b01);
+    assert_eq!(chip8.display[0][2], 0b10);
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.display[0][0], 0b01);
+    assert_eq!(chip8.display[0][1], 0b01);
+    assert_eq!(chip8.display[0][2], 0b00);
+}
+
+#[test]
+fn scroll_up() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0x00;
+    chip8.memory[PROG_START + 1] = 0xD2;
+    chip8.display[2][0] = 1;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.display[0][0], 1);
+    assert_eq!(chip8.display[2][0], 0);
+}
+
+#[test]
+fn load_audio_pattern() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF0;
+    chip8.memory[PROG_START + 1] = 0x02;
+    chip8.memory[PROG_START + 2] = 0xF4;
+    chip8.memory[PROG_START + 3] = 0x3A;
+    for index in 0..16 {
+        chip8.memory[0x300 + index] = index as u8;
+    }
+    chip8.reg_i = 0x300;
+    chip8.reg_v[4] = 100;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.audio().pattern[15], 15);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 4);
+    assert_eq!(chip8.audio().pitch, 100);
+}
+
+fn get_emulator() -> Chip8 {
+    Chip8::new(Quirks::XO_CHIP)
+}
+fn get_emulator_with_quirks(quirks: Quirks) -> Chip8 {
+    Chip8::new(quirks)
+}
+fn get_emulator_with_host() -> Chip8<TestHost> {
+    Chip8::with_host(TestHost::default(), Quirks::XO_CHIP, SplitMix64::default())
+}
+
+/// Records what the emulator told it, and presses whichever keys the test sets.
+#[derive(Default)]
+struct TestHost {
+    keys: [bool; 16],
+    sound: Option<AudioState>,
+    sound_changes: u32,
+    waits: u32,
+    display_changes: u32,
+    /// The number of pixels lit when the display last changed.
+    lit_pixels: usize,
+}
+
+impl Host for TestHost {
+    fn is_key_pressed(&self, key: u8) -> bool {
+        self.keys[key as usize]
+    }
+
+    fn set_sound(&mut self, sound: Option<&AudioState>) {
+        self.sound = sound.copied();
+        self.sound_changes += 1;
+    }
+
+    fn waiting_for_key(&mut self) {
+        self.waits += 1;
+    }
+
+    fn display_changed(&mut self, display: &Display) {
+        self.display_changes += 1;
+        self.lit_pixels = display
+            .rows()
+            .flatten()
+            .filter(|pixel| **pixel != 0)
+            .count();
+    }
+}
+
+#[test]
+fn breaks_on_memory_write_watchpoint() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xF0;
+    chip8.memory[PROG_START + 1] = 0x33;
+    chip8.reg_v[0] = 123;
+    chip8.reg_i = 0x300;
+    chip8
+        .watchpoints_mut()
+        .add(Watchpoint {
+            condition: Condition::Write {
+                start: 0x301,
+                end: 0x301,
+            },
+            action: Action::Break,
+        })
+        .unwrap();
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::HitWatchpoint));
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(
+        chip8.watchpoints().hits().collect::<Vec<_>>(),
+        [&WatchpointHit {
+            index: 0,
+            pc: 0x200,
+            event: Event::Write {
+                address: 0x301,
+                value: 2
+            },
+        }]
+    );
+}
+
+#[test]
+fn logs_memory_read_watchpoint() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START] = 0xD0;
+    chip8.memory[PROG_START + 1] = 0x05;
+    chip8.reg_i = SPRITE_START as u16;
+    chip8
+        .watchpoints_mut()
+        .add(Watchpoint {
+            condition: Condition::Read { start: 4, end: 10 },
+            action: Action::Log,
+        })
+        .unwrap();
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::Executed));
+    assert_eq!(
+        chip8.watchpoints().hits().next().map(|hit| hit.event),
+        Some(Event::Read { address: 4 })
+    );
+
+    // Hits only last until the next instruction.
+    chip8.memory[PROG_START + 2] = 0x60;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.watchpoints().hits().count(), 0);
+}
+
+#[test]
+fn breaks_on_register_watchpoint() {
+    let mut chip8 = get_emulator();
+    chip8.memory[PROG_START..PROG_START + 4].copy_from_slice(&[0x75, 0x01, 0x75, 0x01]);
+    chip8.reg_v[5] = 8;
+    chip8
+        .watchpoints_mut()
+        .add(Watchpoint {
+            condition: Condition::Change {
+                register: Register::V(5),
+                predicate: Predicate::Equals(10),
+            },
+            action: Action::Break,
+        })
+        .unwrap();
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::Executed));
+    assert_eq!(chip8.cycle(), Ok(StepOutcome::HitWatchpoint));
+    assert_eq!(chip8.reg_v[5], 10);
+}
+
+#[test]
+fn restores_saved_state() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::SUPER_CHIP);
+    chip8.load(&[0x00, 0xFF, 0x22, 0x08, 0xD0, 0x15]).unwrap();
+    chip8.reg_v[3] = 0x42;
+    chip8.reg_flags[1] = 7;
+    chip8.reg_timer_delay = 30;
+    chip8.audio.pitch = 100;
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+    chip8.pressed_key = Some(6);
+    let state = chip8.save_state();
+
+    let mut restored = get_emulator();
+    restored.load_state(&state).unwrap();
+    assert_eq!(restored.reg_pc, 0x208);
+    assert_eq!(restored.stack(), [0x202]);
+    assert_eq!(restored.reg_v, chip8.reg_v);
+    assert_eq!(restored.reg_flags, chip8.reg_flags);
+    assert_eq!(restored.reg_timer_delay, chip8.reg_timer_delay);
+    assert_eq!(restored.frame_cycles, 2);
+    assert_eq!(restored.pressed_key, Some(6));
+    assert_eq!(restored.memory, chip8.memory);
+    assert_eq!(restored.display, chip8.display);
+    assert_eq!(restored.audio, chip8.audio);
+    assert_eq!(restored.quirks, Quirks::SUPER_CHIP);
+    assert_eq!(restored.save_state(), state);
+}
+
+#[test]
+fn restores_random_sequence() {
+    let mut chip8 = Chip8::with_rng(Quirks::XO_CHIP, SplitMix64::new(99));
+    chip8.load(&[0xC0, 0xFF, 0x12, 0x00]).unwrap();
+    chip8.cycle().unwrap();
+    let state = chip8.save_state();
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+
+    let mut restored = get_emulator();
+    restored.load_state(&state).unwrap();
+    restored.cycle().unwrap();
+    restored.cycle().unwrap();
+    assert_eq!(restored.reg_v[0], chip8.reg_v[0]);
+}
+
+#[test]
+fn rejects_corrupted_state() {
+    let mut chip8 = get_emulator();
+    chip8.reg_v[0] = 1;
+    let mut state = chip8.save_state();
+    let middle = state.len() / 2;
+    state[middle] ^= 1;
+
+    let mut other = get_emulator();
+    assert_eq!(other.load_state(&state), Err(StateError::ChecksumMismatch));
+    assert_eq!(other.reg_v[0], 0);
+}
+
+#[test]
+fn runs_frame_then_ticks_timers() {
+    let mut chip8 = get_emulator();
+    chip8.load(&[0x70, 0x01, 0x12, 0x00]).unwrap();
+    chip8.reg_timer_delay = 10;
+    let frame = chip8.run_frame(9).unwrap();
+    assert_eq!(frame.outcome, StepOutcome::Executed);
+    assert!(!frame.display_changed);
+    assert_eq!(chip8.reg_v[0], 5);
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    assert_eq!(chip8.reg_timer_delay, 9);
+}
+
+#[test]
+fn run_frame_reports_display_changes() {
+    let mut chip8 = get_emulator();
+    chip8.load(&[0x00, 0xE0, 0x12, 0x02]).unwrap();
+    assert!(chip8.run_frame(4).unwrap().display_changed);
+    assert!(!chip8.run_frame(4).unwrap().display_changed);
+}
+
+#[test]
+fn run_frame_ends_early_when_waiting() {
+    let mut chip8 = get_emulator_with_quirks(Quirks::COSMAC_VIP);
+    chip8.load(&[0xD0, 0x01, 0xD0, 0x01, 0x70, 0x01]).unwrap();
+    let frame = chip8.run_frame(10).unwrap();
+    assert_eq!(frame.outcome, StepOutcome::WaitingForDisplay);
+    assert!(frame.display_changed);
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 2);
+    // The tick at the end of the frame lets the next sprite draw.
+    chip8.run_frame(2).unwrap();
+    assert_eq!(chip8.reg_pc as usize, PROG_START + 6);
+    assert_eq!(chip8.reg_v[0], 1);
+}
+
+#[test]
+fn tells_host_when_sound_starts_and_stops() {
+    let mut chip8 = get_emulator_with_host();
+    chip8.load(&[0xF4, 0x18, 0x12, 0x02]).unwrap();
+    chip8.reg_v[4] = 3;
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.host.sound, Some(chip8.audio));
+    chip8.tick_60hz();
+    assert_eq!(chip8.host.sound, Some(chip8.audio));
+    chip8.tick_60hz();
+    assert_eq!(chip8.host.sound, None);
+    chip8.tick_60hz();
+    assert_eq!(chip8.host.sound_changes, 2);
+}
+
+#[test]
+fn tells_host_when_sound_changes_while_playing() {
+    let mut chip8 = get_emulator_with_host();
+    chip8.load(&[0xF4, 0x18, 0xF5, 0x3A]).unwrap();
+    chip8.reg_v[4] = 10;
+    chip8.reg_v[5] = 200;
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.host.sound.map(|sound| sound.pitch), Some(200));
+    assert_eq!(chip8.host.sound_changes, 2);
+}
+
+#[test]
+fn tells_host_when_display_changes() {
+    let mut chip8 = get_emulator_with_host();
+    chip8
+        .load(&[0xA0, 0x00, 0xD0, 0x05, 0x00, 0xC1, 0x60, 0x01, 0x00, 0xE0])
+        .unwrap();
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.host.display_changes, 0);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.host.display_changes, 1);
+    assert_eq!(chip8.host.lit_pixels, 14);
+    chip8.cycle().unwrap();
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.host.display_changes, 2);
+    chip8.cycle().unwrap();
+    assert_eq!(chip8.host.display_changes, 3);
+    assert_eq!(chip8.host.lit_pixels, 0);
+}
+
+#[test]
+fn marks_display_dirty_until_cleared() {
+    let mut chip8 = get_emulator();
+    chip8.load(&[0x00, 0xE0, 0x60, 0x01]).unwrap();
+    assert!(chip8.is_display_dirty());
+    chip8.clear_display_dirty();
+    chip8.cycle().unwrap();
+    assert!(chip8.is_display_dirty());
+    chip8.clear_display_dirty();
+    chip8.cycle().unwrap();
+    assert!(!chip8.is_display_dirty());
+}
+
+#[test]
+fn saves_state_without_allocating() {
+    let chip8 = get_emulator();
+    let mut buffer = [0; Chip8::<(), SplitMix64>::STATE_SIZE];
+    assert_eq!(chip8.save_state_into(&mut buffer), Ok(buffer.len()));
+    assert_eq!(buffer[..], chip8.save_state()[..]);
+    assert_eq!(
+        chip8.save_state_into(&mut buffer[1..]),
+        Err(StateError::BufferTooSmall {
+            required: buffer.len()
+        })
+    );
+}
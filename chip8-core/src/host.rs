@@ -0,0 +1,27 @@
+use crate::{audio::AudioState, display::Display};
+
+/// Connects the emulator to whatever is running it. Every hook does nothing
+/// by default, so hosts only implement the ones they need. Frontends that
+/// draw once per frame can ignore `display_changed` and read the display with
+/// `Chip8::display` and `Chip8::is_display_dirty` instead.
+pub trait Host {
+    /// Whether `key` (0-F) is held down. Queried by `EX9E`, `EXA1` and `FX0A`.
+    fn is_key_pressed(&self, _key: u8) -> bool {
+        false
+    }
+
+    /// Called when the buzzer starts or stops, or when the sound changes while
+    /// it's playing. `None` means silence.
+    fn set_sound(&mut self, _sound: Option<&AudioState>) {}
+
+    /// Called each time `FX0A` blocks, waiting for a key to be pressed and
+    /// then released.
+    fn waiting_for_key(&mut self) {}
+
+    /// Called after an instruction changes the display: `00E0`, `DXYN`, the
+    /// scrolls and the resolution switches. Also called when a state is loaded.
+    fn display_changed(&mut self, _display: &Display) {}
+}
+
+/// A host with no keys pressed that ignores sound.
+impl Host for () {}
@@ -0,0 +1,343 @@
+use core::fmt;
+
+/// A decoded CHIP-8, SUPER-CHIP or XO-CHIP instruction. Register operands are
+/// V register indices (0-15).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// `00CN`: scroll the selected planes down by N pixels.
+    ScrollDown(u8),
+    /// `00DN`: scroll the selected planes up by N pixels.
+    ScrollUp(u8),
+    /// `00E0`: clear the selected planes.
+    Clear,
+    /// `00EE`: return from a subroutine.
+    Return,
+    /// `00FB`: scroll the selected planes right by 4 pixels.
+    ScrollRight,
+    /// `00FC`: scroll the selected planes left by 4 pixels.
+    ScrollLeft,
+    /// `00FD`: exit the interpreter.
+    Exit,
+    /// `00FE`: switch to 64x32 low resolution mode.
+    Lores,
+    /// `00FF`: switch to 128x64 high resolution mode.
+    Hires,
+    /// `1NNN`: jump to NNN.
+    Jump(u16),
+    /// `2NNN`: call the subroutine at NNN.
+    Call(u16),
+    /// `3XNN`: skip the next instruction if VX == NN.
+    SkipIfEqual(u8, u8),
+    /// `4XNN`: skip the next instruction if VX != NN.
+    SkipIfNotEqual(u8, u8),
+    /// `5XY0`: skip the next instruction if VX == VY.
+    SkipIfRegistersEqual(u8, u8),
+    /// `5XY2`: save VX to VY (in either order) to memory starting at I.
+    SaveRange(u8, u8),
+    /// `5XY3`: load VX to VY (in either order) from memory starting at I.
+    LoadRange(u8, u8),
+    /// `6XNN`: VX := NN.
+    SetConst(u8, u8),
+    /// `7XNN`: VX += NN, without carry.
+    AddConst(u8, u8),
+    /// `8XY0`: VX := VY.
+    Set(u8, u8),
+    /// `8XY1`: VX |= VY.
+    Or(u8, u8),
+    /// `8XY2`: VX &= VY.
+    And(u8, u8),
+    /// `8XY3`: VX ^= VY.
+    Xor(u8, u8),
+    /// `8XY4`: VX += VY, VF := carry.
+    Add(u8, u8),
+    /// `8XY5`: VX -= VY, VF := not borrow.
+    Sub(u8, u8),
+    /// `8XY6`: VX := VY >> 1 (or VX >> 1), VF := shifted out bit.
+    ShiftRight(u8, u8),
+    /// `8XY7`: VX := VY - VX, VF := not borrow.
+    SubReversed(u8, u8),
+    /// `8XYE`: VX := VY << 1 (or VX << 1), VF := shifted out bit.
+    ShiftLeft(u8, u8),
+    /// `9XY0`: skip the next instruction if VX != VY.
+    SkipIfRegistersNotEqual(u8, u8),
+    /// `ANNN`: I := NNN.
+    SetI(u16),
+    /// `BNNN`: jump to NNN + V0 (or NNN + VX).
+    JumpOffset(u16),
+    /// `CXNN`: VX := random byte & NN.
+    Random(u8, u8),
+    /// `DXYN`: draw an N row sprite (or a 16x16 sprite if N is 0) at VX, VY.
+    Draw(u8, u8, u8),
+    /// `EX9E`: skip the next instruction if the key in VX is pressed.
+    SkipIfKey(u8),
+    /// `EXA1`: skip the next instruction if the key in VX is not pressed.
+    SkipIfNotKey(u8),
+    /// `F000 NNNN`: I := NNNN, where NNNN is the following word.
+    SetLongI,
+    /// `FN01`: select the drawing planes in the bitmask N.
+    Plane(u8),
+    /// `F002`: load the 16 byte audio pattern from I.
+    Audio,
+    /// `FX07`: VX := delay timer.
+    GetDelay(u8),
+    /// `FX0A`: wait for a key and store it in VX.
+    WaitKey(u8),
+    /// `FX15`: delay timer := VX.
+    SetDelay(u8),
+    /// `FX18`: sound timer := VX.
+    SetSound(u8),
+    /// `FX1E`: I += VX.
+    AddI(u8),
+    /// `FX29`: I := address of the small font sprite for the digit in VX.
+    Hex(u8),
+    /// `FX30`: I := address of the big font sprite for the digit in VX.
+    BigHex(u8),
+    /// `FX33`: store the binary-coded decimal of VX at I.
+    Bcd(u8),
+    /// `FX3A`: audio pitch := VX.
+    Pitch(u8),
+    /// `FX55`: save V0 to VX to memory starting at I.
+    Save(u8),
+    /// `FX65`: load V0 to VX from memory starting at I.
+    Load(u8),
+    /// `FX75`: save V0 to VX to the flag registers.
+    SaveFlags(u8),
+    /// `FX85`: load V0 to VX from the flag registers.
+    LoadFlags(u8),
+}
+
+/// The opcode does not match any known instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    pub opcode: u16,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unknown opcode {:#06X}", self.opcode)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+impl Instruction {
+    pub fn decode(opcode: u16) -> Result<Instruction, DecodeError> {
+        let x = ((opcode & 0x0F00) >> 8) as u8;
+        let y = ((opcode & 0x00F0) >> 4) as u8;
+        let n = (opcode & 0x000F) as u8;
+        let nn = (opcode & 0x00FF) as u8;
+        let nnn = opcode & 0x0FFF;
+
+        let instruction = match (opcode & 0xF000) >> 12 {
+            0x0 => match opcode {
+                0x00E0 => Instruction::Clear,
+                0x00EE => Instruction::Return,
+                0x00FB => Instruction::ScrollRight,
+                0x00FC => Instruction::ScrollLeft,
+                0x00FD => Instruction::Exit,
+                0x00FE => Instruction::Lores,
+                0x00FF => Instruction::Hires,
+                _ if opcode & 0xFFF0 == 0x00C0 => Instruction::ScrollDown(n),
+                _ if opcode & 0xFFF0 == 0x00D0 => Instruction::ScrollUp(n),
+                _ => return Err(DecodeError { opcode }),
+            },
+            0x1 => Instruction::Jump(nnn),
+            0x2 => Instruction::Call(nnn),
+            0x3 => Instruction::SkipIfEqual(x, nn),
+            0x4 => Instruction::SkipIfNotEqual(x, nn),
+            0x5 => match n {
+                0x0 => Instruction::SkipIfRegistersEqual(x, y),
+                0x2 => Instruction::SaveRange(x, y),
+                0x3 => Instruction::LoadRange(x, y),
+                _ => return Err(DecodeError { opcode }),
+            },
+            0x6 => Instruction::SetConst(x, nn),
+            0x7 => Instruction::AddConst(x, nn),
+            0x8 => match n {
+                0x0 => Instruction::Set(x, y),
+                0x1 => Instruction::Or(x, y),
+                0x2 => Instruction::And(x, y),
+                0x3 => Instruction::Xor(x, y),
+                0x4 => Instruction::Add(x, y),
+                0x5 => Instruction::Sub(x, y),
+                0x6 => Instruction::ShiftRight(x, y),
+                0x7 => Instruction::SubReversed(x, y),
+                0xE => Instruction::ShiftLeft(x, y),
+                _ => return Err(DecodeError { opcode }),
+            },
+            0x9 if n == 0 => Instruction::SkipIfRegistersNotEqual(x, y),
+            0xA => Instruction::SetI(nnn),
+            0xB => Instruction::JumpOffset(nnn),
+            0xC => Instruction::Random(x, nn),
+            0xD => Instruction::Draw(x, y, n),
+            0xE => match nn {
+                0x9E => Instruction::SkipIfKey(x),
+                0xA1 => Instruction::SkipIfNotKey(x),
+                _ => return Err(DecodeError { opcode }),
+            },
+            0xF => match nn {
+                0x00 if x == 0 => Instruction::SetLongI,
+                0x01 => Instruction::Plane(x),
+                0x02 if x == 0 => Instruction::Audio,
+                0x07 => Instruction::GetDelay(x),
+                0x0A => Instruction::WaitKey(x),
+                0x15 => Instruction::SetDelay(x),
+                0x18 => Instruction::SetSound(x),
+                0x1E => Instruction::AddI(x),
+                0x29 => Instruction::Hex(x),
+                0x30 => Instruction::BigHex(x),
+                0x33 => Instruction::Bcd(x),
+                0x3A => Instruction::Pitch(x),
+                0x55 => Instruction::Save(x),
+                0x65 => Instruction::Load(x),
+                0x75 => Instruction::SaveFlags(x),
+                0x85 => Instruction::LoadFlags(x),
+                _ => return Err(DecodeError { opcode }),
+            },
+            _ => return Err(DecodeError { opcode }),
+        };
+
+        Ok(instruction)
+    }
+
+    /// Encodes the instruction back into its opcode. Operands are masked to
+    /// the width of their field.
+    pub fn encode(&self) -> u16 {
+        let xy = |base: u16, x: u8, y: u8| base | (x as u16 & 0xF) << 8 | (y as u16 & 0xF) << 4;
+        let xnn = |base: u16, x: u8, nn: u8| base | (x as u16 & 0xF) << 8 | nn as u16;
+        let x = |base: u16, x: u8| base | (x as u16 & 0xF) << 8;
+
+        match *self {
+            Instruction::ScrollDown(n) => 0x00C0 | (n as u16 & 0xF),
+            Instruction::ScrollUp(n) => 0x00D0 | (n as u16 & 0xF),
+            Instruction::Clear => 0x00E0,
+            Instruction::Return => 0x00EE,
+            Instruction::ScrollRight => 0x00FB,
+            Instruction::ScrollLeft => 0x00FC,
+            Instruction::Exit => 0x00FD,
+            Instruction::Lores => 0x00FE,
+            Instruction::Hires => 0x00FF,
+            Instruction::Jump(nnn) => 0x1000 | (nnn & 0x0FFF),
+            Instruction::Call(nnn) => 0x2000 | (nnn & 0x0FFF),
+            Instruction::SkipIfEqual(vx, nn) => xnn(0x3000, vx, nn),
+            Instruction::SkipIfNotEqual(vx, nn) => xnn(0x4000, vx, nn),
+            Instruction::SkipIfRegistersEqual(vx, vy) => xy(0x5000, vx, vy),
+            Instruction::SaveRange(vx, vy) => xy(0x5002, vx, vy),
+            Instruction::LoadRange(vx, vy) => xy(0x5003, vx, vy),
+            Instruction::SetConst(vx, nn) => xnn(0x6000, vx, nn),
+            Instruction::AddConst(vx, nn) => xnn(0x7000, vx, nn),
+            Instruction::Set(vx, vy) => xy(0x8000, vx, vy),
+            Instruction::Or(vx, vy) => xy(0x8001, vx, vy),
+            Instruction::And(vx, vy) => xy(0x8002, vx, vy),
+            Instruction::Xor(vx, vy) => xy(0x8003, vx, vy),
+            Instruction::Add(vx, vy) => xy(0x8004, vx, vy),
+            Instruction::Sub(vx, vy) => xy(0x8005, vx, vy),
+            Instruction::ShiftRight(vx, vy) => xy(0x8006, vx, vy),
+            Instruction::SubReversed(vx, vy) => xy(0x8007, vx, vy),
+            Instruction::ShiftLeft(vx, vy) => xy(0x800E, vx, vy),
+            Instruction::SkipIfRegistersNotEqual(vx, vy) => xy(0x9000, vx, vy),
+            Instruction::SetI(nnn) => 0xA000 | (nnn & 0x0FFF),
+            Instruction::JumpOffset(nnn) => 0xB000 | (nnn & 0x0FFF),
+            Instruction::Random(vx, nn) => xnn(0xC000, vx, nn),
+            Instruction::Draw(vx, vy, n) => xy(0xD000, vx, vy) | (n as u16 & 0xF),
+            Instruction::SkipIfKey(vx) => x(0xE09E, vx),
+            Instruction::SkipIfNotKey(vx) => x(0xE0A1, vx),
+            Instruction::SetLongI => 0xF000,
+            Instruction::Plane(n) => x(0xF001, n),
+            Instruction::Audio => 0xF002,
+            Instruction::GetDelay(vx) => x(0xF007, vx),
+            Instruction::WaitKey(vx) => x(0xF00A, vx),
+            Instruction::SetDelay(vx) => x(0xF015, vx),
+            Instruction::SetSound(vx) => x(0xF018, vx),
+            Instruction::AddI(vx) => x(0xF01E, vx),
+            Instruction::Hex(vx) => x(0xF029, vx),
+            Instruction::BigHex(vx) => x(0xF030, vx),
+            Instruction::Bcd(vx) => x(0xF033, vx),
+            Instruction::Pitch(vx) => x(0xF03A, vx),
+            Instruction::Save(vx) => x(0xF055, vx),
+            Instruction::Load(vx) => x(0xF065, vx),
+            Instruction::SaveFlags(vx) => x(0xF075, vx),
+            Instruction::LoadFlags(vx) => x(0xF085, vx),
+        }
+    }
+
+    /// The size of the instruction in bytes, including any operand words.
+    pub fn size(&self) -> u16 {
+        match self {
+            Instruction::SetLongI => 4,
+            _ => 2,
+        }
+    }
+}
+
+/// Formats the instruction using Octo's mnemonics. `SetLongI` omits its
+/// operand since it is stored in the following word.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Instruction::ScrollDown(n) => write!(f, "scroll-down {}", n),
+            Instruction::ScrollUp(n) => write!(f, "scroll-up {}", n),
+            Instruction::Clear => write!(f, "clear"),
+            Instruction::Return => write!(f, "return"),
+            Instruction::ScrollRight => write!(f, "scroll-right"),
+            Instruction::ScrollLeft => write!(f, "scroll-left"),
+            Instruction::Exit => write!(f, "exit"),
+            Instruction::Lores => write!(f, "lores"),
+            Instruction::Hires => write!(f, "hires"),
+            Instruction::Jump(nnn) => write!(f, "jump {:#05X}", nnn),
+            Instruction::Call(nnn) => write!(f, ":call {:#05X}", nnn),
+            Instruction::SkipIfEqual(x, nn) => write!(f, "if v{:x} != {:#04X} then", x, nn),
+            Instruction::SkipIfNotEqual(x, nn) => write!(f, "if v{:x} == {:#04X} then", x, nn),
+            Instruction::SkipIfRegistersEqual(x, y) => write!(f, "if v{:x} != v{:x} then", x, y),
+            Instruction::SaveRange(x, y) => write!(f, "save v{:x} - v{:x}", x, y),
+            Instruction::LoadRange(x, y) => write!(f, "load v{:x} - v{:x}", x, y),
+            Instruction::SetConst(x, nn) => write!(f, "v{:x} := {:#04X}", x, nn),
+            Instruction::AddConst(x, nn) => write!(f, "v{:x} += {:#04X}", x, nn),
+            Instruction::Set(x, y) => write!(f, "v{:x} := v{:x}", x, y),
+            Instruction::Or(x, y) => write!(f, "v{:x} |= v{:x}", x, y),
+            Instruction::And(x, y) => write!(f, "v{:x} &= v{:x}", x, y),
+            Instruction::Xor(x, y) => write!(f, "v{:x} ^= v{:x}", x, y),
+            Instruction::Add(x, y) => write!(f, "v{:x} += v{:x}", x, y),
+            Instruction::Sub(x, y) => write!(f, "v{:x} -= v{:x}", x, y),
+            Instruction::ShiftRight(x, y) => write!(f, "v{:x} >>= v{:x}", x, y),
+            Instruction::SubReversed(x, y) => write!(f, "v{:x} =- v{:x}", x, y),
+            Instruction::ShiftLeft(x, y) => write!(f, "v{:x} <<= v{:x}", x, y),
+            Instruction::SkipIfRegistersNotEqual(x, y) => {
+                write!(f, "if v{:x} == v{:x} then", x, y)
+            }
+            Instruction::SetI(nnn) => write!(f, "i := {:#05X}", nnn),
+            Instruction::JumpOffset(nnn) => write!(f, "jump0 {:#05X}", nnn),
+            Instruction::Random(x, nn) => write!(f, "v{:x} := random {:#04X}", x, nn),
+            Instruction::Draw(x, y, n) => write!(f, "sprite v{:x} v{:x} {}", x, y, n),
+            Instruction::SkipIfKey(x) => write!(f, "if v{:x} -key then", x),
+            Instruction::SkipIfNotKey(x) => write!(f, "if v{:x} key then", x),
+            Instruction::SetLongI => write!(f, "i := long"),
+            Instruction::Plane(n) => write!(f, "plane {}", n),
+            Instruction::Audio => write!(f, "audio"),
+            Instruction::GetDelay(x) => write!(f, "v{:x} := delay", x),
+            Instruction::WaitKey(x) => write!(f, "v{:x} := key", x),
+            Instruction::SetDelay(x) => write!(f, "delay := v{:x}", x),
+            Instruction::SetSound(x) => write!(f, "buzzer := v{:x}", x),
+            Instruction::AddI(x) => write!(f, "i += v{:x}", x),
+            Instruction::Hex(x) => write!(f, "i := hex v{:x}", x),
+            Instruction::BigHex(x) => write!(f, "i := bighex v{:x}", x),
+            Instruction::Bcd(x) => write!(f, "bcd v{:x}", x),
+            Instruction::Pitch(x) => write!(f, "pitch := v{:x}", x),
+            Instruction::Save(x) => write!(f, "save v{:x}", x),
+            Instruction::Load(x) => write!(f, "load v{:x}", x),
+            Instruction::SaveFlags(x) => write!(f, "saveflags v{:x}", x),
+            Instruction::LoadFlags(x) => write!(f, "loadflags v{:x}", x),
+        }
+    }
+}
+
+impl From<Instruction> for u16 {
+    fn from(instruction: Instruction) -> u16 {
+        instruction.encode()
+    }
+}
+
+#[cfg(test)]
+#[path = "./instruction_test.rs"]
+mod instruction_test;
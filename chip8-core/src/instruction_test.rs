@@ -0,0 +1,59 @@
+use super::{DecodeError, Instruction};
+
+#[test]
+fn decodes_opcodes() {
+    assert_eq!(Instruction::decode(0x00E0), Ok(Instruction::Clear));
+    assert_eq!(Instruction::decode(0x00C7), Ok(Instruction::ScrollDown(7)));
+    assert_eq!(Instruction::decode(0x1A2B), Ok(Instruction::Jump(0xA2B)));
+    assert_eq!(
+        Instruction::decode(0x5342),
+        Ok(Instruction::SaveRange(3, 4))
+    );
+    assert_eq!(
+        Instruction::decode(0x7F01),
+        Ok(Instruction::AddConst(15, 1))
+    );
+    assert_eq!(
+        Instruction::decode(0x8ABE),
+        Ok(Instruction::ShiftLeft(10, 11))
+    );
+    assert_eq!(Instruction::decode(0xD125), Ok(Instruction::Draw(1, 2, 5)));
+    assert_eq!(Instruction::decode(0xF000), Ok(Instruction::SetLongI));
+    assert_eq!(Instruction::decode(0xF301), Ok(Instruction::Plane(3)));
+    assert_eq!(Instruction::decode(0xF465), Ok(Instruction::Load(4)));
+}
+
+#[test]
+fn rejects_unknown_opcodes() {
+    for opcode in [
+        0x0000, 0x00E1, 0x5341, 0x8008, 0x9001, 0xE000, 0xF100, 0xF102, 0xF0FF,
+    ] {
+        assert_eq!(Instruction::decode(opcode), Err(DecodeError { opcode }));
+    }
+}
+
+#[test]
+fn encodes_every_decoded_opcode() {
+    for opcode in 0..=u16::MAX {
+        if let Ok(instruction) = Instruction::decode(opcode) {
+            assert_eq!(instruction.encode(), opcode, "{:?}", instruction);
+        }
+    }
+}
+
+#[test]
+fn sizes_long_instructions() {
+    assert_eq!(Instruction::SetLongI.size(), 4);
+    assert_eq!(Instruction::Clear.size(), 2);
+}
+
+#[test]
+fn formats_octo_mnemonics() {
+    assert_eq!(
+        Instruction::SkipIfEqual(3, 0x2A).to_string(),
+        "if v3 != 0x2A then"
+    );
+    assert_eq!(Instruction::Draw(10, 11, 5).to_string(), "sprite va vb 5");
+    assert_eq!(Instruction::Call(0x2F0).to_string(), ":call 0x2F0");
+    assert_eq!(Instruction::BigHex(15).to_string(), "i := bighex vf");
+}
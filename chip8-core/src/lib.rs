@@ -0,0 +1,25 @@
+//! The CHIP-8 interpreter and tools, free of any terminal or audio
+//! dependencies so that frontends and other tools can build on them.
+//!
+//! Without the default `std` feature the interpreter builds with `no_std` and
+//! never allocates. The tools that need the heap (the assembler, disassembler
+//! and rewind history) and the audio sample generator are only available with
+//! `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "std")]
+pub mod assembler;
+pub mod audio;
+#[cfg(feature = "std")]
+pub mod disassembler;
+pub mod display;
+pub mod emulator;
+pub mod host;
+pub mod instruction;
+pub mod quirks;
+pub mod random;
+#[cfg(feature = "std")]
+pub mod rewind;
+pub mod save_state;
+pub mod watchpoint;
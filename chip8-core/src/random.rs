@@ -0,0 +1,66 @@
+use crate::save_state::{StateError, StateReader, StateWriter};
+
+/// The random number generator used by `CXNN`. Its state is stored in save
+/// states so that a restored machine produces the same numbers as the original.
+pub trait RandomSource: Sized {
+    /// The bytes written by `write_state`.
+    const STATE_SIZE: usize;
+
+    fn next_byte(&mut self) -> u8;
+
+    fn write_state(&self, writer: &mut StateWriter);
+
+    fn read_state(reader: &mut StateReader) -> Result<Self, StateError>;
+}
+
+/// SplitMix64, a small and fast generator that accepts any seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    /// Seeds the generator from the system clock, so that every run differs.
+    #[cfg(feature = "std")]
+    pub fn from_system_time() -> SplitMix64 {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+        SplitMix64::new(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl RandomSource for SplitMix64 {
+    const STATE_SIZE: usize = 8;
+
+    fn next_byte(&mut self) -> u8 {
+        // The high bits are the best mixed.
+        (self.next_u64() >> 56) as u8
+    }
+
+    fn write_state(&self, writer: &mut StateWriter) {
+        writer.bytes(&self.state.to_be_bytes());
+    }
+
+    fn read_state(reader: &mut StateReader) -> Result<SplitMix64, StateError> {
+        Ok(SplitMix64::new(u64::from_be_bytes(reader.array()?)))
+    }
+}
+
+#[cfg(test)]
+#[path = "./random_test.rs"]
+mod random_test;
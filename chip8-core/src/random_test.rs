@@ -0,0 +1,39 @@
+use super::{RandomSource, SplitMix64};
+use crate::save_state::{StateReader, StateWriter};
+
+#[test]
+fn matches_reference_sequence() {
+    let mut rng = SplitMix64::new(0);
+    assert_eq!(rng.next_u64(), 0xE220A8397B1DCDAF);
+    assert_eq!(rng.next_u64(), 0x6E789E6AA1B965F4);
+}
+
+#[test]
+fn same_seed_produces_same_bytes() {
+    let mut first = SplitMix64::new(1234);
+    let mut second = SplitMix64::new(1234);
+    let mut other = SplitMix64::new(1235);
+    let bytes: Vec<u8> = (0..32).map(|_| first.next_byte()).collect();
+    assert_eq!(
+        bytes,
+        (0..32).map(|_| second.next_byte()).collect::<Vec<u8>>()
+    );
+    assert_ne!(
+        bytes,
+        (0..32).map(|_| other.next_byte()).collect::<Vec<u8>>()
+    );
+}
+
+#[test]
+fn resumes_from_saved_state() {
+    let mut rng = SplitMix64::new(42);
+    rng.next_byte();
+    let mut state = [0; 32];
+    let mut writer = StateWriter::new(&mut state);
+    rng.write_state(&mut writer);
+    let size = writer.finish().unwrap();
+
+    let mut restored =
+        SplitMix64::read_state(&mut StateReader::new(&state[..size]).unwrap()).unwrap();
+    assert_eq!(restored.next_byte(), rng.next_byte());
+}
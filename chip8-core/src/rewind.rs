@@ -0,0 +1,298 @@
+use std::{collections::VecDeque, ops::Range};
+
+use crate::{
+    display::Display,
+    emulator::{Chip8, ExecutionError, Frame, StepOutcome, Writes},
+    host::Host,
+    random::{RandomSource, SplitMix64},
+    save_state::{self, StateWriter, CHECKSUM_SIZE, HEADER_SIZE},
+};
+
+/// A new keyframe is taken after this many frames, which bounds how much has
+/// to be replayed to step back.
+const KEYFRAME_INTERVAL: u32 = 30;
+/// The number of bytes compared at once when looking for changes.
+const DELTA_CHUNK_SIZE: usize = 64;
+/// Changed bytes this close together are stored as one run, which costs less
+/// than the header of another.
+const RUN_GAP: usize = 8;
+
+/// Wraps a `Chip8`, recording its history so that execution can be stepped
+/// backwards one instruction at a time.
+pub struct Rewind<H: Host = (), R: RandomSource = SplitMix64> {
+    emulator: Chip8<H, R>,
+    history: History,
+}
+
+/// The history is split into segments, each starting from a full save state
+/// (a keyframe) and followed by the bytes of the state that each change
+/// after it wrote. Stepping back replays the changes from the nearest
+/// keyframe. The oldest segment is dropped once the rest cover the budget.
+struct History {
+    /// The number of frames to keep. 0 disables recording.
+    budget: u32,
+    /// The state as of the latest change. Only the parts of the machine that
+    /// were written are serialized again, so its checksum is left stale.
+    state: Vec<u8>,
+    /// The next state is written here to be compared with `state`.
+    next: Vec<u8>,
+    segments: VecDeque<Segment>,
+}
+
+struct Segment {
+    keyframe: Vec<u8>,
+    /// Runs of bytes that changed, each as a little-endian `u32` offset into
+    /// the state and a `u32` length followed by the new bytes.
+    runs: Vec<u8>,
+    changes: Vec<Change>,
+    /// Frames run since the keyframe.
+    frames: u32,
+}
+
+struct Change {
+    /// Where the runs of the change end in `Segment::runs`.
+    end: usize,
+    /// The segment's frames as of the change.
+    frames: u32,
+}
+
+impl<H: Host, R: RandomSource> Rewind<H, R> {
+    /// Keeps at least `frames` 60 Hz frames of history, however many
+    /// instructions are run in each. A history of 0 frames disables recording.
+    pub fn new(mut emulator: Chip8<H, R>, frames: u32) -> Rewind<H, R> {
+        emulator.take_writes();
+        let state = if frames > 0 {
+            emulator.save_state()
+        } else {
+            Vec::new()
+        };
+        Rewind {
+            history: History {
+                budget: frames,
+                next: state.clone(),
+                segments: VecDeque::from([Segment::new(state.clone())]),
+                state,
+            },
+            emulator,
+        }
+    }
+
+    pub fn emulator(&self) -> &Chip8<H, R> {
+        &self.emulator
+    }
+
+    /// Changes made through this are recorded along with the next instruction,
+    /// so rewinding past that instruction undoes them too.
+    pub fn emulator_mut(&mut self) -> &mut Chip8<H, R> {
+        &mut self.emulator
+    }
+
+    pub fn into_inner(self) -> Chip8<H, R> {
+        self.emulator
+    }
+
+    /// Runs `Chip8::cycle`, recording the instruction if it changed the machine.
+    pub fn cycle(&mut self) -> Result<StepOutcome, ExecutionError> {
+        let outcome = self.emulator.cycle()?;
+        self.history.record(&mut self.emulator);
+        Ok(outcome)
+    }
+
+    /// Runs `Chip8::step`, recording the instruction if it changed the machine.
+    pub fn step(&mut self) -> Result<StepOutcome, ExecutionError> {
+        let outcome = self.emulator.step()?;
+        self.history.record(&mut self.emulator);
+        Ok(outcome)
+    }
+
+    /// Runs `Chip8::tick_60hz`. The tick is recorded on its own, so stepping
+    /// back over it counts as an instruction.
+    pub fn tick_60hz(&mut self) {
+        self.emulator.tick_60hz();
+        self.history.record(&mut self.emulator);
+    }
+
+    /// Runs `Chip8::run_frame`, recording each instruction and then the tick.
+    pub fn run_frame(&mut self, cycles: u16) -> Result<Frame, ExecutionError> {
+        let history = &mut self.history;
+        let frame = self
+            .emulator
+            .run_frame_with(cycles, |emulator| history.record(emulator))?;
+        self.history.record(&mut self.emulator);
+        Ok(frame)
+    }
+
+    /// The number of instructions that can currently be stepped back.
+    pub fn history_len(&self) -> usize {
+        self.history
+            .segments
+            .iter()
+            .map(|segment| segment.changes.len())
+            .sum()
+    }
+
+    /// Steps back up to `count` instructions, returning how many were undone.
+    pub fn step_back(&mut self, count: usize) -> usize {
+        let len = self.history_len();
+        let count = usize::min(count, len);
+        if count == 0 {
+            return 0;
+        }
+
+        self.history.rewind_to(len - count);
+        self.emulator
+            .load_state(&self.history.state)
+            .expect("To restore a recorded state.");
+        // The history already holds the state that was loaded.
+        self.emulator.take_writes();
+        count
+    }
+}
+
+impl History {
+    fn record<H: Host, R: RandomSource>(&mut self, emulator: &mut Chip8<H, R>) {
+        let writes = emulator.take_writes();
+        if self.budget == 0 {
+            return;
+        }
+
+        let mut writer = StateWriter::new(&mut self.next);
+        emulator.write_state(&mut writer, &writes);
+        let segment = self.segments.back_mut().unwrap();
+        let start = segment.runs.len();
+        for region in Self::regions::<H, R>(&writes) {
+            record_runs(
+                &self.state[region.clone()],
+                &self.next[region.clone()],
+                region.start,
+                &mut segment.runs,
+            );
+            self.state[region.clone()].copy_from_slice(&self.next[region]);
+        }
+
+        segment.frames += writes.frames;
+        if segment.runs.len() > start {
+            segment.changes.push(Change {
+                end: segment.runs.len(),
+                frames: segment.frames,
+            });
+        }
+        if segment.frames >= KEYFRAME_INTERVAL {
+            self.segments.push_back(Segment::new(self.state.clone()));
+            self.drop_old_segments();
+        }
+    }
+
+    /// The parts of the state that `writes` may have changed: everything
+    /// besides memory and the display, which are only included where written.
+    fn regions<H: Host, R: RandomSource>(writes: &Writes) -> [Range<usize>; 4] {
+        let memory = Chip8::<H, R>::MEMORY_STATE_OFFSET;
+        let display = Chip8::<H, R>::DISPLAY_STATE_OFFSET;
+        let display_end = display + Display::STATE_SIZE;
+        [
+            HEADER_SIZE..memory,
+            memory + writes.memory.start..memory + writes.memory.end,
+            if writes.display {
+                display..display_end
+            } else {
+                display..display
+            },
+            display_end..Chip8::<H, R>::STATE_SIZE - CHECKSUM_SIZE,
+        ]
+    }
+
+    /// Drops the oldest segments while the rest still cover the budget.
+    fn drop_old_segments(&mut self) {
+        let mut frames: u32 = self.segments.iter().map(|segment| segment.frames).sum();
+        while let Some(oldest) = self.segments.front() {
+            if frames - oldest.frames < self.budget {
+                break;
+            }
+            frames -= oldest.frames;
+            self.segments.pop_front();
+        }
+    }
+
+    /// Forgets everything after the first `len` changes, leaving `state` as it
+    /// was then with a valid checksum.
+    fn rewind_to(&mut self, len: usize) {
+        let mut before = 0;
+        let mut index = 0;
+        while before + self.segments[index].changes.len() < len {
+            before += self.segments[index].changes.len();
+            index += 1;
+        }
+        self.segments.truncate(index + 1);
+
+        let segment = &mut self.segments[index];
+        segment.changes.truncate(len - before);
+        let last = segment.changes.last();
+        segment.runs.truncate(last.map_or(0, |change| change.end));
+        segment.frames = last.map_or(0, |change| change.frames);
+
+        self.state.copy_from_slice(&segment.keyframe);
+        let mut runs = &segment.runs[..];
+        while !runs.is_empty() {
+            let offset = u32::from_le_bytes(runs[0..4].try_into().unwrap()) as usize;
+            let length = u32::from_le_bytes(runs[4..8].try_into().unwrap()) as usize;
+            self.state[offset..offset + length].copy_from_slice(&runs[8..8 + length]);
+            runs = &runs[8 + length..];
+        }
+        save_state::seal(&mut self.state);
+    }
+}
+
+impl Segment {
+    fn new(keyframe: Vec<u8>) -> Segment {
+        Segment {
+            keyframe,
+            runs: Vec::new(),
+            changes: Vec::new(),
+            frames: 0,
+        }
+    }
+}
+
+/// Appends the runs of bytes in `new` that differ from `old`, where both
+/// start at `offset` in the state.
+fn record_runs(old: &[u8], new: &[u8], offset: usize, runs: &mut Vec<u8>) {
+    let mut run: Option<Range<usize>> = None;
+    // Most of the state is unchanged, so whole chunks are compared first.
+    for (index, (old_chunk, new_chunk)) in old
+        .chunks(DELTA_CHUNK_SIZE)
+        .zip(new.chunks(DELTA_CHUNK_SIZE))
+        .enumerate()
+    {
+        if old_chunk == new_chunk {
+            continue;
+        }
+        let chunk_start = index * DELTA_CHUNK_SIZE;
+        for (position, (old_byte, new_byte)) in old_chunk.iter().zip(new_chunk).enumerate() {
+            if old_byte == new_byte {
+                continue;
+            }
+            let position = chunk_start + position;
+            match &mut run {
+                Some(run) if position - run.end <= RUN_GAP => run.end = position + 1,
+                _ => {
+                    if let Some(run) = run.replace(position..position + 1) {
+                        push_run(runs, offset + run.start, &new[run]);
+                    }
+                }
+            }
+        }
+    }
+    if let Some(run) = run {
+        push_run(runs, offset + run.start, &new[run]);
+    }
+}
+
+fn push_run(runs: &mut Vec<u8>, offset: usize, bytes: &[u8]) {
+    runs.extend_from_slice(&(offset as u32).to_le_bytes());
+    runs.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    runs.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+#[path = "./rewind_test.rs"]
+mod rewind_test;
@@ -0,0 +1,139 @@
+use super::Rewind;
+use crate::{emulator::Chip8, quirks::Quirks};
+
+/// Counts up in V0 forever.
+fn get_rewind(frames: u32) -> Rewind {
+    let mut chip8 = Chip8::new(Quirks::default());
+    chip8.load(&[0x70, 0x01, 0x12, 0x00]).unwrap();
+    Rewind::new(chip8, frames)
+}
+
+#[test]
+fn steps_back_instructions() {
+    let mut rewind = get_rewind(60);
+    for _ in 0..6 {
+        rewind.cycle().unwrap();
+    }
+    assert_eq!(rewind.emulator().registers()[0], 3);
+    assert_eq!(rewind.history_len(), 6);
+
+    assert_eq!(rewind.step_back(1), 1);
+    assert_eq!(rewind.emulator().pc(), 0x202);
+    assert_eq!(rewind.emulator().registers()[0], 3);
+
+    assert_eq!(rewind.step_back(3), 3);
+    assert_eq!(rewind.emulator().pc(), 0x200);
+    assert_eq!(rewind.emulator().registers()[0], 1);
+
+    // Running again continues from the rewound state.
+    rewind.cycle().unwrap();
+    rewind.cycle().unwrap();
+    assert_eq!(rewind.emulator().registers()[0], 2);
+    assert_eq!(rewind.history_len(), 4);
+}
+
+#[test]
+fn stops_at_the_start_of_history() {
+    let mut rewind = get_rewind(60);
+    rewind.cycle().unwrap();
+    assert_eq!(rewind.step_back(5), 1);
+    assert_eq!(rewind.emulator().pc(), 0x200);
+    assert_eq!(rewind.emulator().registers()[0], 0);
+    assert_eq!(rewind.step_back(1), 0);
+}
+
+#[test]
+fn drops_history_beyond_budget() {
+    let mut rewind = get_rewind(40);
+    for _ in 0..200 {
+        rewind.run_frame(2).unwrap();
+    }
+    // Whole keyframe intervals of 30 frames are dropped, as long as what's
+    // left covers the budget, so the last 80 frames are kept.
+    assert_eq!(rewind.history_len(), 80 * 2);
+    assert_eq!(rewind.step_back(1000), 80 * 2);
+    assert_eq!(rewind.emulator().registers()[0], 120);
+    assert_eq!(rewind.step_back(1), 0);
+}
+
+#[test]
+fn keeps_the_budget_at_any_speed() {
+    let mut rewind = get_rewind(40);
+    for _ in 0..200 {
+        rewind.run_frame(100).unwrap();
+    }
+    assert_eq!(rewind.step_back(usize::MAX), 80 * 100);
+    assert_eq!(rewind.emulator().registers()[0], (120 * 50 % 256) as u8);
+}
+
+#[test]
+fn replays_memory_and_display_changes() {
+    // Stores V0 as BCD, saves V0-V2, draws the digits and scrolls, in a loop.
+    let mut chip8 = Chip8::new(Quirks::SUPER_CHIP);
+    chip8
+        .load(&[
+            0x70, 0x07, // v0 += 7
+            0xA3, 0x00, // i := 0x300
+            0xF0, 0x33, // bcd v0
+            0xF2, 0x55, // save v2
+            0xF1, 0x29, // i := hex v1
+            0xD1, 0x25, // sprite v1 v2 5
+            0x00, 0xC1, // scroll-down 1
+            0x12, 0x00, // jump 0x200
+        ])
+        .unwrap();
+    let mut rewind = Rewind::new(chip8, 60);
+    let mut states = Vec::new();
+    for _ in 0..250 {
+        states.push(rewind.emulator().save_state());
+        rewind.cycle().unwrap();
+    }
+    for state in states.iter().rev() {
+        rewind.step_back(1);
+        assert_eq!(&rewind.emulator().save_state(), state);
+    }
+}
+
+#[test]
+fn undoes_changes_made_between_instructions() {
+    let mut rewind = get_rewind(60);
+    rewind.cycle().unwrap();
+    rewind.emulator_mut().load(&[0x70, 0x02]).unwrap();
+    rewind.cycle().unwrap();
+    rewind.step_back(1);
+    assert_eq!(rewind.emulator().registers()[0], 1);
+    assert_eq!(rewind.emulator().pc(), 0x202);
+    assert_eq!(rewind.emulator().save_state(), {
+        let mut expected = get_rewind(0);
+        expected.cycle().unwrap();
+        expected.into_inner().save_state()
+    });
+}
+
+#[test]
+fn records_nothing_without_a_budget() {
+    let mut rewind = get_rewind(0);
+    rewind.cycle().unwrap();
+    assert_eq!(rewind.history_len(), 0);
+    assert_eq!(rewind.step_back(1), 0);
+}
+
+#[test]
+fn records_each_instruction_in_a_frame() {
+    let mut chip8 = Chip8::new(Quirks::default());
+    chip8
+        .load(&[0x60, 0x05, 0xF0, 0x15, 0x70, 0x01, 0x12, 0x04])
+        .unwrap();
+    let mut rewind = Rewind::new(chip8, 60);
+    rewind.run_frame(4).unwrap();
+    assert_eq!(rewind.emulator().registers()[0], 6);
+    assert_eq!(rewind.emulator().delay_timer(), 4);
+    assert_eq!(rewind.history_len(), 5);
+
+    assert_eq!(rewind.step_back(1), 1);
+    assert_eq!(rewind.emulator().delay_timer(), 5);
+    assert_eq!(rewind.emulator().registers()[0], 6);
+    assert_eq!(rewind.step_back(2), 2);
+    assert_eq!(rewind.emulator().registers()[0], 5);
+    assert_eq!(rewind.emulator().pc(), 0x204);
+}
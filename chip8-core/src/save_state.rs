@@ -0,0 +1,198 @@
+use core::fmt;
+
+/// Identifies a save state file.
+const MAGIC: &[u8; 4] = b"C8ST";
+/// Bumped whenever the layout changes. Older states are rejected rather than misread.
+pub const VERSION: u16 = 5;
+pub(crate) const HEADER_SIZE: usize = MAGIC.len() + 2;
+pub(crate) const CHECKSUM_SIZE: usize = 4;
+/// The bytes in every save state besides its fields.
+pub(crate) const OVERHEAD_SIZE: usize = HEADER_SIZE + CHECKSUM_SIZE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    /// The data doesn't start with the save state magic number.
+    NotASaveState,
+    /// The state was written by an incompatible version of the emulator.
+    UnsupportedVersion { version: u16 },
+    /// The data was corrupted or truncated.
+    ChecksumMismatch,
+    /// The data passed the checksum but doesn't describe a valid machine.
+    InvalidData,
+    /// The buffer given to write the state into can't hold all of it.
+    BufferTooSmall { required: usize },
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            StateError::NotASaveState => write!(f, "Not a save state"),
+            StateError::UnsupportedVersion { version } => write!(
+                f,
+                "Save state version {} is not supported (expected {})",
+                version, VERSION
+            ),
+            StateError::ChecksumMismatch => write!(f, "Save state is corrupted"),
+            StateError::InvalidData => write!(f, "Save state contains invalid data"),
+            StateError::BufferTooSmall { required } => {
+                write!(f, "Save state needs a buffer of {} bytes", required)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StateError {}
+
+/// Builds a save state in a caller's buffer: a header, the fields in the
+/// order they're written, then a checksum of everything before it. Values are
+/// big-endian.
+pub struct StateWriter<'a> {
+    buffer: &'a mut [u8],
+    /// The bytes written so far, which may run past the end of the buffer.
+    len: usize,
+}
+
+impl<'a> StateWriter<'a> {
+    pub fn new(buffer: &'a mut [u8]) -> StateWriter<'a> {
+        let mut writer = StateWriter { buffer, len: 0 };
+        writer.bytes(MAGIC);
+        writer.u16(VERSION);
+        writer
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes(&[value]);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_be_bytes());
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    /// Anything that doesn't fit in the buffer is dropped, and reported by `finish`.
+    pub fn bytes(&mut self, values: &[u8]) {
+        let end = self.len + values.len();
+        if let Some(destination) = self.buffer.get_mut(self.len..end) {
+            destination.copy_from_slice(values);
+        }
+        self.len = end;
+    }
+
+    /// Moves past `count` bytes, leaving what was in the buffer there.
+    pub(crate) fn skip(&mut self, count: usize) {
+        self.len += count;
+    }
+
+    /// Appends the checksum and returns the size of the state.
+    pub fn finish(self) -> Result<usize, StateError> {
+        let size = self.len + CHECKSUM_SIZE;
+        if size > self.buffer.len() {
+            return Err(StateError::BufferTooSmall { required: size });
+        }
+        let checksum = checksum(&self.buffer[..self.len]);
+        self.buffer[self.len..size].copy_from_slice(&checksum.to_be_bytes());
+        Ok(size)
+    }
+}
+
+/// Reads back the fields of a save state, in the order they were written.
+pub struct StateReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    /// Checks the header and checksum before any fields are read.
+    pub fn new(data: &'a [u8]) -> Result<StateReader<'a>, StateError> {
+        if data.len() < HEADER_SIZE + CHECKSUM_SIZE || !data.starts_with(MAGIC) {
+            return Err(StateError::NotASaveState);
+        }
+
+        let version = u16::from_be_bytes([data[4], data[5]]);
+        if version != VERSION {
+            return Err(StateError::UnsupportedVersion { version });
+        }
+
+        let (contents, stored_checksum) = data.split_at(data.len() - CHECKSUM_SIZE);
+        if checksum(contents).to_be_bytes() != stored_checksum {
+            return Err(StateError::ChecksumMismatch);
+        }
+
+        Ok(StateReader {
+            data: &contents[HEADER_SIZE..],
+        })
+    }
+
+    pub fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, StateError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn bool(&mut self) -> Result<bool, StateError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(StateError::InvalidData),
+        }
+    }
+
+    pub fn bytes(&mut self, count: usize) -> Result<&'a [u8], StateError> {
+        if count > self.data.len() {
+            return Err(StateError::InvalidData);
+        }
+        let (bytes, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], StateError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+        Ok(array)
+    }
+
+    /// Fails if anything is left over, which means the layout didn't match.
+    pub fn finish(self) -> Result<(), StateError> {
+        if !self.data.is_empty() {
+            return Err(StateError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+/// Rewrites the checksum at the end of a state whose fields have been changed
+/// in place.
+pub(crate) fn seal(state: &mut [u8]) {
+    let (contents, stored_checksum) = state.split_at_mut(state.len() - CHECKSUM_SIZE);
+    stored_checksum.copy_from_slice(&checksum(contents).to_be_bytes());
+}
+
+/// Adler-32, which is quick to compute over a whole state.
+fn checksum(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    // The most bytes that can be summed before `b` could overflow.
+    const BLOCK_SIZE: usize = 5552;
+
+    let (mut a, mut b) = (1, 0);
+    for block in data.chunks(BLOCK_SIZE) {
+        for byte in block {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+    b << 16 | a
+}
+
+#[cfg(test)]
+#[path = "./save_state_test.rs"]
+mod save_state_test;
@@ -0,0 +1,90 @@
+use super::{StateError, StateReader, StateWriter, VERSION};
+
+fn write_sample(buffer: &mut [u8]) -> Result<usize, StateError> {
+    let mut writer = StateWriter::new(buffer);
+    writer.u8(0x12);
+    writer.u16(0x3456);
+    writer.bool(true);
+    writer.bytes(&[7, 8, 9]);
+    writer.finish()
+}
+
+fn sample_state() -> Vec<u8> {
+    let mut buffer = [0; 32];
+    let size = write_sample(&mut buffer).unwrap();
+    buffer[..size].to_vec()
+}
+
+#[test]
+fn rejects_small_buffers() {
+    let mut buffer = [0; 16];
+    assert_eq!(
+        write_sample(&mut buffer),
+        Err(StateError::BufferTooSmall { required: 17 })
+    );
+    assert_eq!(write_sample(&mut [0; 17]), Ok(17));
+}
+
+#[test]
+fn reads_back_written_fields() {
+    let state = sample_state();
+    let mut reader = StateReader::new(&state).unwrap();
+    assert_eq!(reader.u8(), Ok(0x12));
+    assert_eq!(reader.u16(), Ok(0x3456));
+    assert_eq!(reader.bool(), Ok(true));
+    assert_eq!(reader.array(), Ok([7, 8, 9]));
+    assert_eq!(reader.finish(), Ok(()));
+}
+
+#[test]
+fn rejects_other_data() {
+    assert_eq!(
+        StateReader::new(b"not a state").err(),
+        Some(StateError::NotASaveState)
+    );
+    assert_eq!(StateReader::new(&[]).err(), Some(StateError::NotASaveState));
+}
+
+#[test]
+fn rejects_other_versions() {
+    let mut state = sample_state();
+    state[4..6].copy_from_slice(&(VERSION + 1).to_be_bytes());
+    assert_eq!(
+        StateReader::new(&state).err(),
+        Some(StateError::UnsupportedVersion {
+            version: VERSION + 1
+        })
+    );
+}
+
+#[test]
+fn rejects_corrupted_data() {
+    let mut state = sample_state();
+    state[7] ^= 0xFF;
+    assert_eq!(
+        StateReader::new(&state).err(),
+        Some(StateError::ChecksumMismatch)
+    );
+
+    let mut state = sample_state();
+    state.remove(8);
+    assert_eq!(
+        StateReader::new(&state).err(),
+        Some(StateError::ChecksumMismatch)
+    );
+}
+
+#[test]
+fn rejects_mismatched_layouts() {
+    let state = sample_state();
+
+    let mut reader = StateReader::new(&state).unwrap();
+    assert_eq!(reader.bytes(100), Err(StateError::InvalidData));
+
+    let mut reader = StateReader::new(&state).unwrap();
+    reader.u16().unwrap();
+    assert_eq!(reader.bool(), Err(StateError::InvalidData));
+
+    let reader = StateReader::new(&state).unwrap();
+    assert_eq!(reader.finish(), Err(StateError::InvalidData));
+}
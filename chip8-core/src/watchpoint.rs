@@ -0,0 +1,262 @@
+use core::fmt;
+
+/// The most watchpoints that can be set at once.
+pub const MAX_WATCHPOINTS: usize = 16;
+
+/// A register that can be watched for changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    /// V0 to VF. Indices past 15 are rejected by `Watchpoints::add`.
+    V(u8),
+    I,
+}
+
+/// Which new values of a watched register trigger the watchpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Predicate {
+    Any,
+    Equals(u16),
+    GreaterThan(u16),
+    LessThan(u16),
+}
+
+impl Predicate {
+    pub fn matches(&self, value: u16) -> bool {
+        match *self {
+            Predicate::Any => true,
+            Predicate::Equals(expected) => value == expected,
+            Predicate::GreaterThan(limit) => value > limit,
+            Predicate::LessThan(limit) => value < limit,
+        }
+    }
+}
+
+/// What to watch for. Address ranges are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    /// Memory in the range is written by `FX55`, `5XY2` or `FX33`.
+    Write { start: u16, end: u16 },
+    /// Memory in the range is read by `DXYN`, `FX65`, `5XY3` or `F002`.
+    Read { start: u16, end: u16 },
+    /// The register changes to a value matching the predicate.
+    Change {
+        register: Register,
+        predicate: Predicate,
+    },
+}
+
+/// Whether a triggered watchpoint should stop execution or only be recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Break,
+    Log,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub condition: Condition,
+    pub action: Action,
+}
+
+/// Why a watchpoint couldn't be added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchpointError {
+    /// All `MAX_WATCHPOINTS` slots are in use.
+    Full,
+    /// The watchpoint is on a V register that doesn't exist.
+    InvalidRegister { index: u8 },
+}
+
+impl fmt::Display for WatchpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            WatchpointError::Full => write!(f, "All watchpoint slots are in use"),
+            WatchpointError::InvalidRegister { index } => {
+                write!(f, "V{} is not a register; expected V0 to VF", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WatchpointError {}
+
+/// What happened to trigger a watchpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// The first address in the watched range that was read.
+    Read {
+        address: u16,
+    },
+    /// The first address in the watched range that was written, and its new value.
+    Write {
+        address: u16,
+        value: u8,
+    },
+    Change {
+        old: u16,
+        new: u16,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchpointHit {
+    /// The index returned by `Watchpoints::add`.
+    pub index: usize,
+    /// The address of the instruction that triggered the watchpoint.
+    pub pc: u16,
+    pub event: Event,
+}
+
+/// A memory access made by a single instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Access {
+    Read { start: u16, len: usize },
+    Write { start: u16, len: usize },
+}
+
+/// The registers that can be watched, captured before and after an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RegisterSnapshot {
+    pub v: [u8; 16],
+    pub i: u16,
+}
+
+impl RegisterSnapshot {
+    fn get(&self, register: Register) -> u16 {
+        match register {
+            Register::V(x) => self.v[x as usize] as u16,
+            Register::I => self.i,
+        }
+    }
+}
+
+/// The watchpoints set on a `Chip8` and the ones hit by the last instruction.
+#[derive(Debug, Clone, Default)]
+pub struct Watchpoints {
+    entries: [Option<Watchpoint>; MAX_WATCHPOINTS],
+    hits: [Option<WatchpointHit>; MAX_WATCHPOINTS],
+}
+
+impl Watchpoints {
+    pub fn new() -> Watchpoints {
+        Watchpoints {
+            entries: [None; MAX_WATCHPOINTS],
+            hits: [None; MAX_WATCHPOINTS],
+        }
+    }
+
+    /// Adds a watchpoint, returning its index.
+    pub fn add(&mut self, watchpoint: Watchpoint) -> Result<usize, WatchpointError> {
+        if let Condition::Change {
+            register: Register::V(index),
+            ..
+        } = watchpoint.condition
+        {
+            if index > 0xF {
+                return Err(WatchpointError::InvalidRegister { index });
+            }
+        }
+        let index = self
+            .entries
+            .iter()
+            .position(Option::is_none)
+            .ok_or(WatchpointError::Full)?;
+        self.entries[index] = Some(watchpoint);
+        Ok(index)
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Watchpoint> {
+        self.entries.get_mut(index)?.take()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries = [None; MAX_WATCHPOINTS];
+    }
+
+    /// Iterates over the watchpoints that are set, along with their indices.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Watchpoint)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| entry.as_ref().map(|watchpoint| (index, watchpoint)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(Option::is_none)
+    }
+
+    /// The watchpoints triggered by the last instruction executed.
+    pub fn hits(&self) -> impl Iterator<Item = &WatchpointHit> {
+        self.hits.iter().flatten()
+    }
+
+    /// Whether any watchpoint triggered by the last instruction should stop execution.
+    pub fn should_break(&self) -> bool {
+        self.hits().any(|hit| {
+            matches!(
+                self.entries[hit.index],
+                Some(Watchpoint {
+                    action: Action::Break,
+                    ..
+                })
+            )
+        })
+    }
+
+    pub(crate) fn clear_hits(&mut self) {
+        self.hits = [None; MAX_WATCHPOINTS];
+    }
+
+    /// Records hits for an instruction at `pc` that made `access` and changed
+    /// the registers from `old` to `new`.
+    pub(crate) fn check(
+        &mut self,
+        pc: u16,
+        access: Option<Access>,
+        memory: &[u8],
+        old: &RegisterSnapshot,
+        new: &RegisterSnapshot,
+    ) {
+        for (index, entry) in self.entries.iter().enumerate() {
+            let event = match (entry.map(|watchpoint| watchpoint.condition), access) {
+                (Some(Condition::Read { start, end }), Some(Access::Read { start: from, len })) => {
+                    first_overlap(start, end, from, len).map(|address| Event::Read { address })
+                }
+                (
+                    Some(Condition::Write { start, end }),
+                    Some(Access::Write { start: from, len }),
+                ) => first_overlap(start, end, from, len).map(|address| Event::Write {
+                    address,
+                    value: memory[address as usize],
+                }),
+                (
+                    Some(Condition::Change {
+                        register,
+                        predicate,
+                    }),
+                    _,
+                ) => {
+                    let (old, new) = (old.get(register), new.get(register));
+                    (old != new && predicate.matches(new)).then_some(Event::Change { old, new })
+                }
+                _ => None,
+            };
+            self.hits[index] = event.map(|event| WatchpointHit { index, pc, event });
+        }
+    }
+}
+
+/// Returns the first address of `from..from + len` inside `start..=end`.
+fn first_overlap(start: u16, end: u16, from: u16, len: usize) -> Option<u16> {
+    if len == 0 {
+        return None;
+    }
+    let first = usize::max(start as usize, from as usize);
+    let last = usize::min(end as usize, from as usize + len - 1);
+    (first <= last).then_some(first as u16)
+}
+
+#[cfg(test)]
+#[path = "./watchpoint_test.rs"]
+mod watchpoint_test;
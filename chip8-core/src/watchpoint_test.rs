@@ -0,0 +1,161 @@
+use super::{
+    Access, Action, Condition, Event, Predicate, Register, RegisterSnapshot, Watchpoint,
+    WatchpointError, WatchpointHit, Watchpoints, MAX_WATCHPOINTS,
+};
+
+fn watch(condition: Condition) -> Watchpoint {
+    Watchpoint {
+        condition,
+        action: Action::Break,
+    }
+}
+
+#[test]
+fn adds_and_removes_watchpoints() {
+    let mut watchpoints = Watchpoints::new();
+    assert!(watchpoints.is_empty());
+
+    let read = watch(Condition::Read {
+        start: 0x300,
+        end: 0x30F,
+    });
+    for index in 0..MAX_WATCHPOINTS {
+        assert_eq!(watchpoints.add(read), Ok(index));
+    }
+    assert_eq!(watchpoints.add(read), Err(WatchpointError::Full));
+
+    assert_eq!(watchpoints.remove(3), Some(read));
+    assert_eq!(watchpoints.remove(3), None);
+    assert_eq!(watchpoints.iter().count(), MAX_WATCHPOINTS - 1);
+    assert_eq!(watchpoints.add(read), Ok(3));
+
+    watchpoints.clear();
+    assert!(watchpoints.is_empty());
+}
+
+#[test]
+fn rejects_registers_past_vf() {
+    let mut watchpoints = Watchpoints::new();
+    let change = |index| {
+        watch(Condition::Change {
+            register: Register::V(index),
+            predicate: Predicate::Any,
+        })
+    };
+    assert_eq!(watchpoints.add(change(0xF)), Ok(0));
+    assert_eq!(
+        watchpoints.add(change(0x10)),
+        Err(WatchpointError::InvalidRegister { index: 0x10 })
+    );
+    assert_eq!(watchpoints.iter().count(), 1);
+}
+
+#[test]
+fn matches_overlapping_memory_accesses() {
+    let mut watchpoints = Watchpoints::new();
+    watchpoints
+        .add(watch(Condition::Write {
+            start: 0x302,
+            end: 0x304,
+        }))
+        .unwrap();
+    let registers = RegisterSnapshot { v: [0; 16], i: 0 };
+    let mut memory = [0; 0x400];
+    memory[0x302] = 9;
+
+    watchpoints.check(
+        0x200,
+        Some(Access::Write {
+            start: 0x300,
+            len: 3,
+        }),
+        &memory,
+        &registers,
+        &registers,
+    );
+    assert_eq!(
+        watchpoints.hits().collect::<Vec<_>>(),
+        [&WatchpointHit {
+            index: 0,
+            pc: 0x200,
+            event: Event::Write {
+                address: 0x302,
+                value: 9
+            },
+        }]
+    );
+    assert!(watchpoints.should_break());
+
+    // Reads and writes before the range don't count.
+    for access in [
+        Access::Read {
+            start: 0x302,
+            len: 1,
+        },
+        Access::Write {
+            start: 0x300,
+            len: 2,
+        },
+    ] {
+        watchpoints.check(0x200, Some(access), &memory, &registers, &registers);
+        assert_eq!(watchpoints.hits().count(), 0);
+    }
+}
+
+#[test]
+fn matches_register_predicates() {
+    let mut watchpoints = Watchpoints::new();
+    watchpoints
+        .add(Watchpoint {
+            condition: Condition::Change {
+                register: Register::V(3),
+                predicate: Predicate::GreaterThan(10),
+            },
+            action: Action::Log,
+        })
+        .unwrap();
+    watchpoints
+        .add(watch(Condition::Change {
+            register: Register::I,
+            predicate: Predicate::Any,
+        }))
+        .unwrap();
+
+    let before = RegisterSnapshot { v: [0; 16], i: 0 };
+    let mut after = before;
+    after.v[3] = 11;
+    watchpoints.check(0x200, None, &[], &before, &after);
+    assert_eq!(
+        watchpoints.hits().collect::<Vec<_>>(),
+        [&WatchpointHit {
+            index: 0,
+            pc: 0x200,
+            event: Event::Change { old: 0, new: 11 },
+        }]
+    );
+    assert!(!watchpoints.should_break());
+
+    after.v[3] = 5;
+    after.i = 0x300;
+    watchpoints.check(0x202, None, &[], &before, &after);
+    assert_eq!(
+        watchpoints.hits().map(|hit| hit.index).collect::<Vec<_>>(),
+        [1]
+    );
+    assert!(watchpoints.should_break());
+
+    // Setting a register to the value it already has isn't a change.
+    watchpoints.check(0x204, None, &[], &after, &after);
+    assert_eq!(watchpoints.hits().count(), 0);
+}
+
+#[test]
+fn evaluates_predicates() {
+    assert!(Predicate::Any.matches(0));
+    assert!(Predicate::Equals(5).matches(5));
+    assert!(!Predicate::Equals(5).matches(6));
+    assert!(Predicate::GreaterThan(5).matches(6));
+    assert!(!Predicate::GreaterThan(5).matches(5));
+    assert!(Predicate::LessThan(5).matches(4));
+    assert!(!Predicate::LessThan(5).matches(5));
+}
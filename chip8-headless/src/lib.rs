@@ -0,0 +1,5 @@
+//! Runs ROMs without a terminal or audio device, for use in automated tests.
+
+pub mod report;
+pub mod runner;
+pub mod script;
@@ -0,0 +1,151 @@
+use std::{env, fs, path::Path, process};
+
+use chip8_core::{
+    emulator::{Chip8, DEFAULT_CYCLES_PER_FRAME},
+    quirks::Quirks,
+    random::SplitMix64,
+};
+use chip8_headless::{
+    report,
+    runner::{self, Stop},
+    script::{KeyScript, ScriptHost},
+};
+
+/// Ten seconds of emulated time.
+const DEFAULT_FRAMES: u32 = 600;
+
+const USAGE: &str = "Usage: chip8-headless <rom.ch8> [--frames N] [--instructions-per-frame N] \
+[--keys script.txt] [--quirks preset] [--seed N] [--display out.txt|out.pbm] [--registers out.json]";
+
+struct Options {
+    rom_path: String,
+    frames: u32,
+    instructions_per_frame: u16,
+    keys_path: Option<String>,
+    quirks: Quirks,
+    seed: u64,
+    display_path: Option<String>,
+    registers_path: Option<String>,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = parse_args(&args).unwrap_or_else(|error| {
+        eprintln!("{}\n{}", error, USAGE);
+        process::exit(2);
+    });
+
+    let rom = fs::read(&options.rom_path)
+        .unwrap_or_else(|error| fail(&format!("Unable to read {}: {}", options.rom_path, error)));
+    let script = match &options.keys_path {
+        Some(path) => {
+            let source = fs::read_to_string(path)
+                .unwrap_or_else(|error| fail(&format!("Unable to read {}: {}", path, error)));
+            KeyScript::parse(&source).unwrap_or_else(|error| fail(&format!("{}: {}", path, error)))
+        }
+        None => KeyScript::default(),
+    };
+
+    let mut emulator = Chip8::with_host(
+        ScriptHost::new(script),
+        options.quirks,
+        SplitMix64::new(options.seed),
+    );
+    if let Err(error) = emulator.load(&rom) {
+        fail(error);
+    }
+    let run = runner::run(
+        &mut emulator,
+        options.frames,
+        options.instructions_per_frame,
+    );
+
+    let is_pbm = options
+        .display_path
+        .as_ref()
+        .is_some_and(|path| Path::new(path).extension().is_some_and(|ext| ext == "pbm"));
+    let display = if is_pbm {
+        report::display_pbm(emulator.display())
+    } else {
+        report::display_text(emulator.display())
+    };
+    let registers = report::registers_json(&emulator, &run);
+    // Anything without a file to go to is printed.
+    for (path, contents) in [
+        (&options.display_path, display),
+        (&options.registers_path, registers),
+    ] {
+        match path {
+            Some(path) => write_file(path, &contents),
+            None => print!("{}", contents),
+        }
+    }
+
+    if let Stop::Error(error) = run.stop {
+        fail(&format!("Emulation stopped: {}", error));
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut rom_path = None;
+    let mut frames = DEFAULT_FRAMES;
+    let mut instructions_per_frame = DEFAULT_CYCLES_PER_FRAME;
+    let mut keys_path = None;
+    let mut quirks = Quirks::default();
+    let mut seed = 0;
+    let mut display_path = None;
+    let mut registers_path = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or(format!("Expected a value after {}.", arg))
+        };
+        match arg.as_str() {
+            "--frames" => frames = parse_number(&value()?)?,
+            "--instructions-per-frame" => instructions_per_frame = parse_number(&value()?)?,
+            "--keys" => keys_path = Some(value()?),
+            "--quirks" => {
+                let name = value()?;
+                quirks = Quirks::from_preset_name(&name).ok_or(format!(
+                    "Unknown quirks preset '{}'. Expected one of: vip, chip48, schip, xochip.",
+                    name
+                ))?;
+            }
+            "--seed" => seed = parse_number(&value()?)?,
+            "--display" => display_path = Some(value()?),
+            "--registers" => registers_path = Some(value()?),
+            _ if rom_path.is_none() => rom_path = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument '{}'.", arg)),
+        }
+    }
+
+    Ok(Options {
+        rom_path: rom_path.ok_or("Expected a path to the ROM to run.")?,
+        frames,
+        instructions_per_frame,
+        keys_path,
+        quirks,
+        seed,
+        display_path,
+        registers_path,
+    })
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("Invalid number '{}'.", text))
+}
+
+fn write_file(path: &str, contents: &str) {
+    if let Err(error) = fs::write(path, contents) {
+        fail(&format!("Unable to write {}: {}", path, error));
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
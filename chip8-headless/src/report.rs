@@ -0,0 +1,66 @@
+use std::fmt::Write;
+
+use chip8_core::{display::Display, emulator::Chip8, host::Host, random::RandomSource};
+
+use crate::runner::{Run, Stop};
+
+/// Characters for each pixel value: unlit, then lit on the first, second or
+/// both XO-CHIP planes.
+const PIXEL_CHARS: [char; 4] = ['.', '#', '+', '*'];
+/// Plain PBM lines can't be longer than 70 characters, so longer rows are
+/// split after this many pixels.
+const PBM_PIXELS_PER_LINE: usize = 32;
+
+/// The display as text, one line per row.
+pub fn display_text(display: &Display) -> String {
+    let mut text = String::new();
+    for row in display.rows() {
+        text.extend(row.iter().map(|pixel| PIXEL_CHARS[*pixel as usize & 0b11]));
+        text.push('\n');
+    }
+    text
+}
+
+/// The display as a plain PBM image. Pixels lit on any plane are black.
+pub fn display_pbm(display: &Display) -> String {
+    let mut pbm = format!("P1\n{} {}\n", display.width(), display.height());
+    for row in display.rows() {
+        for line in row.chunks(PBM_PIXELS_PER_LINE) {
+            let pixels: Vec<&str> = line
+                .iter()
+                .map(|pixel| if *pixel != 0 { "1" } else { "0" })
+                .collect();
+            pbm.push_str(&pixels.join(" "));
+            pbm.push('\n');
+        }
+    }
+    pbm
+}
+
+/// The registers and how the run ended, as a JSON object.
+pub fn registers_json<H: Host, R: RandomSource>(emulator: &Chip8<H, R>, run: &Run) -> String {
+    let mut json = String::from("{\n");
+    writeln!(json, "  \"frames\": {},", run.frames).unwrap();
+    writeln!(json, "  \"stop\": \"{}\",", run.stop.name()).unwrap();
+    if let Stop::Error(error) = run.stop {
+        let message = error.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+        writeln!(json, "  \"error\": \"{}\",", message).unwrap();
+    }
+    writeln!(json, "  \"pc\": {},", emulator.pc()).unwrap();
+    writeln!(json, "  \"i\": {},", emulator.i()).unwrap();
+    writeln!(json, "  \"v\": {},", json_array(emulator.registers())).unwrap();
+    writeln!(json, "  \"delay_timer\": {},", emulator.delay_timer()).unwrap();
+    writeln!(json, "  \"sound_timer\": {},", emulator.sound_timer()).unwrap();
+    writeln!(json, "  \"stack\": {}", json_array(emulator.stack())).unwrap();
+    json.push_str("}\n");
+    json
+}
+
+fn json_array<T: ToString>(values: &[T]) -> String {
+    let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+    format!("[{}]", values.join(", "))
+}
+
+#[cfg(test)]
+#[path = "./report_test.rs"]
+mod report_test;
@@ -0,0 +1,66 @@
+use chip8_core::{display::Display, emulator::Chip8, quirks::Quirks};
+
+use super::{display_pbm, display_text, registers_json};
+use crate::runner::{Run, Stop};
+
+fn get_display() -> Display {
+    let mut display = Display::new();
+    display[0][1] = 1;
+    display[1][0] = 2;
+    display[1][2] = 3;
+    display
+}
+
+#[test]
+fn formats_display_as_text() {
+    let text = display_text(&get_display());
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 32);
+    assert_eq!(&lines[0][..4], ".#..");
+    assert_eq!(&lines[1][..4], "+.*.");
+    assert_eq!(lines[2], ".".repeat(64));
+}
+
+#[test]
+fn formats_display_as_pbm() {
+    let pbm = display_pbm(&get_display());
+    let lines: Vec<&str> = pbm.lines().collect();
+    assert_eq!(lines[0], "P1");
+    assert_eq!(lines[1], "64 32");
+    assert_eq!(lines.len(), 2 + 32 * 2);
+    assert_eq!(&lines[2][..8], "0 1 0 0 ");
+    assert_eq!(&lines[4][..8], "1 0 1 0 ");
+}
+
+#[test]
+fn keeps_pbm_lines_within_70_characters() {
+    let mut display = get_display();
+    display.set_hires(true);
+    display[63][127] = 1;
+    let pbm = display_pbm(&display);
+    let lines: Vec<&str> = pbm.lines().collect();
+    assert_eq!(lines[1], "128 64");
+    assert!(lines.iter().all(|line| line.len() <= 70));
+    let pixels: Vec<&str> = lines[2..].iter().flat_map(|line| line.split(' ')).collect();
+    assert_eq!(pixels.len(), 128 * 64);
+    assert_eq!(pixels.last(), Some(&"1"));
+}
+
+#[test]
+fn formats_registers_as_json() {
+    let mut chip8 = Chip8::new(Quirks::default());
+    chip8.load(&[0x63, 0x2A, 0xA1, 0x23, 0x22, 0x08]).unwrap();
+    for _ in 0..3 {
+        chip8.step().unwrap();
+    }
+    let run = Run {
+        frames: 1,
+        stop: Stop::FrameLimit,
+    };
+    assert_eq!(
+        registers_json(&chip8, &run),
+        "{\n  \"frames\": 1,\n  \"stop\": \"frame-limit\",\n  \"pc\": 520,\n  \"i\": 291,\n  \
+         \"v\": [0, 0, 0, 42, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],\n  \"delay_timer\": 0,\n  \
+         \"sound_timer\": 0,\n  \"stack\": [516]\n}\n"
+    );
+}
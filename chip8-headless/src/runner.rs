@@ -0,0 +1,80 @@
+use chip8_core::{
+    emulator::{Chip8, ExecutionError, StepOutcome},
+    instruction::Instruction,
+    random::RandomSource,
+};
+
+use crate::script::ScriptHost;
+
+/// Why a run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// The requested number of frames ran.
+    FrameLimit,
+    /// The program reached a jump to itself, which is how most ROMs halt.
+    Looping,
+    /// The program exited with `00FD`.
+    Exited,
+    Error(ExecutionError),
+}
+
+impl Stop {
+    /// A short name for reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stop::FrameLimit => "frame-limit",
+            Stop::Looping => "looping",
+            Stop::Exited => "exited",
+            Stop::Error(_) => "error",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    /// The number of frames run, including the one that stopped the run.
+    pub frames: u32,
+    pub stop: Stop,
+}
+
+/// Runs up to `frames` frames, pressing the host's scripted keys, and stops
+/// early if the program halts.
+pub fn run<R: RandomSource>(
+    emulator: &mut Chip8<ScriptHost, R>,
+    frames: u32,
+    instructions_per_frame: u16,
+) -> Run {
+    for frame in 0..frames {
+        emulator.host_mut().start_frame(frame);
+        let stop = match emulator.run_frame(instructions_per_frame) {
+            Ok(result) if result.outcome == StepOutcome::Exited => Some(Stop::Exited),
+            Ok(_) if is_looping(emulator) => Some(Stop::Looping),
+            Ok(_) => None,
+            Err(error) => Some(Stop::Error(error)),
+        };
+        if let Some(stop) = stop {
+            return Run {
+                frames: frame + 1,
+                stop,
+            };
+        }
+    }
+    Run {
+        frames,
+        stop: Stop::FrameLimit,
+    }
+}
+
+fn is_looping<R: RandomSource>(emulator: &Chip8<ScriptHost, R>) -> bool {
+    let pc = emulator.pc();
+    let memory = emulator.memory();
+    let opcode = match memory.get(pc as usize..pc as usize + 2) {
+        Some(bytes) => u16::from_be_bytes([bytes[0], bytes[1]]),
+        None => return false,
+    };
+    Instruction::decode(opcode) == Ok(Instruction::Jump(pc))
+}
+
+#[cfg(test)]
+#[path = "./runner_test.rs"]
+mod runner_test;
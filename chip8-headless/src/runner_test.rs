@@ -0,0 +1,62 @@
+use chip8_core::{emulator::Chip8, quirks::Quirks, random::SplitMix64};
+
+use super::{run, Run, Stop};
+use crate::script::{KeyScript, ScriptHost};
+
+fn get_emulator(rom: &[u8], script: &str) -> Chip8<ScriptHost> {
+    let host = ScriptHost::new(KeyScript::parse(script).unwrap());
+    let mut chip8 = Chip8::with_host(host, Quirks::default(), SplitMix64::default());
+    chip8.load(rom).unwrap();
+    chip8
+}
+
+#[test]
+fn stops_at_frame_limit() {
+    let mut chip8 = get_emulator(&[0x70, 0x01, 0x12, 0x00], "");
+    assert_eq!(
+        run(&mut chip8, 3, 10),
+        Run {
+            frames: 3,
+            stop: Stop::FrameLimit
+        }
+    );
+    assert_eq!(chip8.registers()[0], 15);
+}
+
+#[test]
+fn stops_when_looping() {
+    let mut chip8 = get_emulator(&[0x60, 0x01, 0x12, 0x02], "");
+    assert_eq!(
+        run(&mut chip8, 100, 10),
+        Run {
+            frames: 1,
+            stop: Stop::Looping
+        }
+    );
+}
+
+#[test]
+fn stops_on_exit() {
+    let mut chip8 = get_emulator(&[0x00, 0xFD], "");
+    assert_eq!(run(&mut chip8, 100, 10).stop, Stop::Exited);
+}
+
+#[test]
+fn stops_on_errors() {
+    let mut chip8 = get_emulator(&[0x00, 0xEE], "");
+    assert!(matches!(run(&mut chip8, 100, 10).stop, Stop::Error(_)));
+}
+
+#[test]
+fn presses_scripted_keys() {
+    // Wait for a key, then halt. The key is released, and taken, in frame 6.
+    let mut chip8 = get_emulator(&[0xF0, 0x0A, 0x12, 0x02], "5 7");
+    assert_eq!(
+        run(&mut chip8, 100, 10),
+        Run {
+            frames: 7,
+            stop: Stop::Looping
+        }
+    );
+    assert_eq!(chip8.registers()[0], 7);
+}
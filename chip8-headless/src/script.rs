@@ -0,0 +1,103 @@
+use chip8_core::host::Host;
+
+/// Keys to hold down during particular frames, read from a file like:
+///
+/// ```text
+/// # frame key [frames]
+/// 10 5      # hold 5 during frame 10
+/// 20 A 3    # hold A during frames 20, 21 and 22
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyScript {
+    presses: Vec<Press>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Press {
+    key: u8,
+    start: u32,
+    /// The first frame the key is no longer held.
+    end: u32,
+}
+
+impl KeyScript {
+    pub fn parse(source: &str) -> Result<KeyScript, String> {
+        let mut presses = Vec::new();
+        for (index, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("");
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.is_empty() {
+                continue;
+            }
+            let press =
+                parse_press(&fields).map_err(|error| format!("Line {}: {}", index + 1, error))?;
+            presses.push(press);
+        }
+        Ok(KeyScript { presses })
+    }
+
+    /// The keys held during `frame`.
+    pub fn keys_at(&self, frame: u32) -> [bool; 16] {
+        let mut keys = [false; 16];
+        for press in &self.presses {
+            if (press.start..press.end).contains(&frame) {
+                keys[press.key as usize] = true;
+            }
+        }
+        keys
+    }
+}
+
+fn parse_press(fields: &[&str]) -> Result<Press, String> {
+    let (frame, key, frames) = match fields {
+        [frame, key] => (frame, key, "1"),
+        [frame, key, frames] => (frame, key, *frames),
+        _ => return Err("Expected a frame, a key and optionally a number of frames.".to_string()),
+    };
+    let start: u32 = frame
+        .parse()
+        .map_err(|_| format!("Invalid frame '{}'.", frame))?;
+    let key = u8::from_str_radix(key, 16)
+        .ok()
+        .filter(|key| *key < 16)
+        .ok_or(format!("Invalid key '{}'. Expected 0-F.", key))?;
+    let frames: u32 = frames
+        .parse()
+        .map_err(|_| format!("Invalid number of frames '{}'.", frames))?;
+    Ok(Press {
+        key,
+        start,
+        end: start.saturating_add(frames),
+    })
+}
+
+/// Presses the keys of a `KeyScript`, one frame at a time.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptHost {
+    script: KeyScript,
+    keys: [bool; 16],
+}
+
+impl ScriptHost {
+    pub fn new(script: KeyScript) -> ScriptHost {
+        ScriptHost {
+            script,
+            keys: [false; 16],
+        }
+    }
+
+    /// Holds the keys scripted for `frame`.
+    pub fn start_frame(&mut self, frame: u32) {
+        self.keys = self.script.keys_at(frame);
+    }
+}
+
+impl Host for ScriptHost {
+    fn is_key_pressed(&self, key: u8) -> bool {
+        self.keys[key as usize]
+    }
+}
+
+#[cfg(test)]
+#[path = "./script_test.rs"]
+mod script_test;
@@ -0,0 +1,31 @@
+use super::KeyScript;
+
+#[test]
+fn holds_keys_for_scripted_frames() {
+    let script = KeyScript::parse("# Start the game\n2 5\n3 a 2 # Then move\n\n").unwrap();
+    assert_eq!(script.keys_at(1), [false; 16]);
+    let mut expected = [false; 16];
+    expected[5] = true;
+    assert_eq!(script.keys_at(2), expected);
+    let mut expected = [false; 16];
+    expected[10] = true;
+    assert_eq!(script.keys_at(3), expected);
+    assert_eq!(script.keys_at(4), expected);
+    assert_eq!(script.keys_at(5), [false; 16]);
+}
+
+#[test]
+fn rejects_invalid_lines() {
+    assert_eq!(
+        KeyScript::parse("1 5\nx 5").err(),
+        Some("Line 2: Invalid frame 'x'.".to_string())
+    );
+    assert_eq!(
+        KeyScript::parse("1 10").err(),
+        Some("Line 1: Invalid key '10'. Expected 0-F.".to_string())
+    );
+    assert_eq!(
+        KeyScript::parse("1").err(),
+        Some("Line 1: Expected a frame, a key and optionally a number of frames.".to_string())
+    );
+}
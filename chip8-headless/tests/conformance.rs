@@ -0,0 +1,147 @@
+//! Runs the test ROMs in `tests/roms` under each quirks preset and compares
+//! the final display with the golden image for that ROM and preset, in
+//! `tests/golden/<rom>/<preset>.txt`. The goldens come from what each ROM is
+//! documented to show, never from output recorded from this emulator.
+//!
+//! The flags and keypad ROMs check themselves and end on the same pass screen
+//! on every platform. The quirks ROM draws a digit for each quirk it detects,
+//! so its goldens follow the documented behaviour of each platform, in the
+//! order it probes them: VF reset, shifting VY, how far FX55 and FX65 move I
+//! (0, X or X + 1), jumping with VX, clipping sprites and waiting for the
+//! display.
+//!
+//! - vip: `1 1 2 0 1 1`. The original interpreter resets VF after 8XY1-8XY3,
+//!   shifts VY, leaves I past the last register, clips sprites and draws once
+//!   per frame.
+//! - chip48: `0 0 1 1 1 0`. CHIP-48 shifts VX in place, leaves I on the last
+//!   register, and jumps with VX. Sprites are still clipped.
+//! - schip: `0 0 0 1 1 0`. SUPER-CHIP 1.1 is CHIP-48 without the increment
+//!   of I.
+//! - xochip: `0 1 2 0 0 0`. XO-CHIP goes back to the VIP's shifts and
+//!   increment, but wraps sprites and draws without waiting.
+
+use std::{fmt::Write, fs, path::PathBuf};
+
+use chip8_core::{assembler, emulator::Chip8, quirks::Quirks, random::SplitMix64};
+use chip8_headless::{
+    report::display_text,
+    runner::{self, Stop},
+    script::{KeyScript, ScriptHost},
+};
+
+const PRESETS: [&str; 4] = ["vip", "chip48", "schip", "xochip"];
+/// Every test ROM halts within two seconds.
+const FRAMES: u32 = 120;
+const INSTRUCTIONS_PER_FRAME: u16 = 1000;
+
+#[test]
+fn flags() {
+    check("flags.8o", "");
+}
+
+#[test]
+fn quirks() {
+    check("quirks.8o", "");
+}
+
+#[test]
+fn keypad() {
+    check("keypad.8o", "2 5\n10 A 10");
+}
+
+fn tests_directory() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+/// The display `rom` should end on under `preset`.
+fn read_golden(rom: &str, preset: &str) -> String {
+    let name = rom.rsplit_once('.').map_or(rom, |(name, _)| name);
+    let path = tests_directory()
+        .join("golden")
+        .join(name)
+        .join(format!("{}.txt", preset));
+    fs::read_to_string(&path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error))
+}
+
+/// Runs `rom` under every preset with `keys` as its key script, and panics
+/// if it doesn't halt or doesn't end on that preset's golden display.
+fn check(rom: &str, keys: &str) {
+    let path = tests_directory().join("roms").join(rom);
+    // `.8o` sources are assembled, anything else is loaded as it is.
+    let data = if rom.ends_with(".8o") {
+        let source = fs::read_to_string(&path).unwrap();
+        assembler::assemble(&source).unwrap_or_else(|error| panic!("{}: {}", rom, error))
+    } else {
+        fs::read(&path).unwrap()
+    };
+    let script = KeyScript::parse(keys).unwrap();
+
+    let mut failures = String::new();
+    for preset in PRESETS {
+        let quirks = Quirks::from_preset_name(preset).unwrap();
+        let mut emulator =
+            Chip8::with_host(ScriptHost::new(script.clone()), quirks, SplitMix64::new(0));
+        emulator.load(&data).unwrap();
+        let run = runner::run(&mut emulator, FRAMES, INSTRUCTIONS_PER_FRAME);
+        if run.stop != Stop::Looping {
+            writeln!(
+                failures,
+                "{} ({}): stopped with {:?} after {} frames",
+                rom, preset, run.stop, run.frames
+            )
+            .unwrap();
+            continue;
+        }
+
+        let difference =
+            compare_display(&read_golden(rom, preset), &display_text(emulator.display()));
+        if !difference.is_empty() {
+            writeln!(failures, "{} ({}) failed:", rom, preset).unwrap();
+            failures.push_str(&difference);
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures);
+}
+
+fn compare_display(expected: &str, actual: &str) -> String {
+    if expected == actual {
+        String::new()
+    } else {
+        diff(expected, actual)
+    }
+}
+
+/// Lists each row that differs, expected above actual, with a caret under
+/// every pixel that changed.
+fn diff(expected: &str, actual: &str) -> String {
+    let mut expected_rows = expected.lines();
+    let mut actual_rows = actual.lines();
+    let mut result = String::new();
+    for row in 0.. {
+        let (expected_row, actual_row) = match (expected_rows.next(), actual_rows.next()) {
+            (None, None) => break,
+            (expected_row, actual_row) => (expected_row.unwrap_or(""), actual_row.unwrap_or("")),
+        };
+        if expected_row == actual_row {
+            continue;
+        }
+
+        let expected_pixels: Vec<char> = expected_row.chars().collect();
+        let actual_pixels: Vec<char> = actual_row.chars().collect();
+        let markers: String = (0..expected_pixels.len().max(actual_pixels.len()))
+            .map(|x| {
+                if expected_pixels.get(x) == actual_pixels.get(x) {
+                    ' '
+                } else {
+                    '^'
+                }
+            })
+            .collect();
+        writeln!(result, "  row {}:", row).unwrap();
+        writeln!(result, "    expected {}", expected_row).unwrap();
+        writeln!(result, "    actual   {}", actual_row).unwrap();
+        writeln!(result, "             {}", markers.trim_end()).unwrap();
+    }
+    result
+}
@@ -0,0 +1,76 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chip8_core::audio::{AudioState, SampleGenerator};
+use rodio::Source;
+
+const SAMPLE_RATE: u32 = 44100;
+const CHUNK_SIZE: usize = 256;
+
+/// What the emulator currently wants to play, shared with the audio thread.
+#[derive(Debug, Clone, Copy)]
+pub struct Sound {
+    pub audio: AudioState,
+    pub is_playing: bool,
+}
+
+/// A never-ending rodio source that renders the shared `Sound`. Samples are
+/// generated in chunks so the lock is only taken every `CHUNK_SIZE` samples.
+pub struct AudioOutput {
+    sound: Arc<Mutex<Sound>>,
+    generator: SampleGenerator,
+    buffer: [f32; CHUNK_SIZE],
+    index: usize,
+}
+
+impl AudioOutput {
+    pub fn new(sound: Arc<Mutex<Sound>>) -> AudioOutput {
+        AudioOutput {
+            sound,
+            generator: SampleGenerator::new(),
+            buffer: [0.0; CHUNK_SIZE],
+            index: CHUNK_SIZE,
+        }
+    }
+}
+
+impl Iterator for AudioOutput {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.index == CHUNK_SIZE {
+            let sound = *self.sound.lock().expect("To lock the sound state.");
+            self.generator.fill(
+                &sound.audio,
+                sound.is_playing,
+                &mut self.buffer,
+                SAMPLE_RATE,
+            );
+            self.index = 0;
+        }
+
+        let sample = self.buffer[self.index];
+        self.index += 1;
+        Some(sample)
+    }
+}
+
+impl Source for AudioOutput {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
@@ -0,0 +1,502 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use chip8_core::{
+    emulator::{Chip8, ExecutionError, StepOutcome},
+    host::Host,
+    instruction::Instruction,
+    rewind::Rewind,
+    watchpoint::{Action, Condition, Event, Predicate, Register, Watchpoint, WatchpointHit},
+};
+
+/// The number of bytes printed per line of a memory dump.
+const HEXDUMP_WIDTH: usize = 16;
+const DEFAULT_HEXDUMP_LENGTH: u16 = 64;
+/// The number of instructions shown either side of the PC.
+const DEFAULT_DISASSEMBLY_CONTEXT: u16 = 5;
+
+const HELP: &str = "\
+Commands:
+  step [N], s [N]          Execute N instructions (default 1).
+  back [N]                 Step back N instructions (default 1).
+  continue, c              Run until a breakpoint is hit. Press Ctrl+B to pause.
+  break ADDR, b ADDR       Break when the PC reaches ADDR.
+  break-opcode PATTERN     Break on opcodes matching PATTERN, e.g. DXYN or 00E?.
+  watch WATCH              Break when WATCH triggers, where WATCH is one of:
+                             read ADDR [END]       memory in ADDR..=END is read
+                             write ADDR [END]      memory in ADDR..=END is written
+                             vX|i [== | > | < N]   the register changes (to a matching value)
+  log WATCH                Print a message when WATCH triggers without breaking.
+  unwatch N                Remove watchpoint N.
+  delete                   Remove all breakpoints and watchpoints.
+  registers, r             Print V0-VF, I, PC, SP, the timers and the stack.
+  memory ADDR [LEN], m     Print a hex dump of LEN bytes (default 64) from ADDR.
+  disassemble [N], d [N]   Disassemble N instructions either side of the PC (default 5).
+  quit, q                  Stop the emulator.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Step(u32),
+    Back(usize),
+    Continue,
+    Break(u16),
+    BreakOpcode(OpcodePattern),
+    Watch(Watchpoint),
+    Unwatch(usize),
+    Delete,
+    Registers,
+    Memory { start: u16, length: u16 },
+    Disassemble(u16),
+    Help,
+    Quit,
+}
+
+/// What the emulator should do once the debugger prompt returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resume {
+    Run,
+    Quit,
+}
+
+/// An opcode with some nibbles left as wildcards. Hex digits must match
+/// exactly, anything else (`X`, `Y`, `N`, `?`) matches any nibble.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodePattern {
+    mask: u16,
+    value: u16,
+}
+
+impl OpcodePattern {
+    pub fn parse(pattern: &str) -> Result<OpcodePattern, String> {
+        if pattern.chars().count() != 4 {
+            return Err(format!(
+                "Expected a 4 nibble opcode pattern but found '{}'.",
+                pattern
+            ));
+        }
+
+        let mut mask = 0;
+        let mut value = 0;
+        for c in pattern.chars() {
+            mask <<= 4;
+            value <<= 4;
+            if let Some(digit) = c.to_digit(16) {
+                mask |= 0xF;
+                value |= digit as u16;
+            }
+        }
+        Ok(OpcodePattern { mask, value })
+    }
+
+    pub fn matches(&self, opcode: u16) -> bool {
+        opcode & self.mask == self.value
+    }
+}
+
+impl fmt::Display for OpcodePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for shift in [12, 8, 4, 0] {
+            if (self.mask >> shift) & 0xF == 0 {
+                write!(f, "?")?;
+            } else {
+                write!(f, "{:X}", (self.value >> shift) & 0xF)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_command(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let command = match words.as_slice() {
+        ["step" | "s"] => Command::Step(1),
+        ["step" | "s", count] => Command::Step(
+            count
+                .parse()
+                .map_err(|_| format!("Invalid step count '{}'.", count))?,
+        ),
+        ["back"] => Command::Back(1),
+        ["back", count] => Command::Back(
+            count
+                .parse()
+                .map_err(|_| format!("Invalid step count '{}'.", count))?,
+        ),
+        ["continue" | "c"] => Command::Continue,
+        ["break" | "b", address] => Command::Break(parse_number(address)?),
+        ["break-opcode", pattern] => Command::BreakOpcode(OpcodePattern::parse(pattern)?),
+        ["watch", watch @ ..] => Command::Watch(Watchpoint {
+            condition: parse_condition(watch)?,
+            action: Action::Break,
+        }),
+        ["log", watch @ ..] => Command::Watch(Watchpoint {
+            condition: parse_condition(watch)?,
+            action: Action::Log,
+        }),
+        ["unwatch", index] => Command::Unwatch(parse_number(index)? as usize),
+        ["delete"] => Command::Delete,
+        ["registers" | "r"] => Command::Registers,
+        ["memory" | "m", start] => Command::Memory {
+            start: parse_number(start)?,
+            length: DEFAULT_HEXDUMP_LENGTH,
+        },
+        ["memory" | "m", start, length] => Command::Memory {
+            start: parse_number(start)?,
+            length: parse_number(length)?,
+        },
+        ["disassemble" | "d"] => Command::Disassemble(DEFAULT_DISASSEMBLY_CONTEXT),
+        ["disassemble" | "d", count] => Command::Disassemble(parse_number(count)?),
+        ["help" | "h" | "?"] => Command::Help,
+        ["quit" | "q"] => Command::Quit,
+        _ => {
+            return Err(format!(
+                "Unknown command '{}'. Type 'help' for a list.",
+                line.trim()
+            ))
+        }
+    };
+    Ok(command)
+}
+
+fn parse_condition(words: &[&str]) -> Result<Condition, String> {
+    let condition = match words {
+        ["read", start] => Condition::Read {
+            start: parse_number(start)?,
+            end: parse_number(start)?,
+        },
+        ["read", start, end] => Condition::Read {
+            start: parse_number(start)?,
+            end: parse_number(end)?,
+        },
+        ["write", start] => Condition::Write {
+            start: parse_number(start)?,
+            end: parse_number(start)?,
+        },
+        ["write", start, end] => Condition::Write {
+            start: parse_number(start)?,
+            end: parse_number(end)?,
+        },
+        [register] => Condition::Change {
+            register: parse_register(register)?,
+            predicate: Predicate::Any,
+        },
+        [register, operator, value] => {
+            let value = parse_number(value)?;
+            Condition::Change {
+                register: parse_register(register)?,
+                predicate: match *operator {
+                    "==" => Predicate::Equals(value),
+                    ">" => Predicate::GreaterThan(value),
+                    "<" => Predicate::LessThan(value),
+                    _ => return Err(format!("Unknown comparison '{}'.", operator)),
+                },
+            }
+        }
+        _ => {
+            return Err(String::from(
+                "Expected 'read', 'write' or a register to watch.",
+            ))
+        }
+    };
+    Ok(condition)
+}
+
+fn parse_register(text: &str) -> Result<Register, String> {
+    if text == "i" {
+        return Ok(Register::I);
+    }
+    text.strip_prefix('v')
+        .filter(|digit| digit.len() == 1)
+        .and_then(|digit| u8::from_str_radix(digit, 16).ok())
+        .map(Register::V)
+        .ok_or(format!("Expected a register but found '{}'.", text))
+}
+
+/// Numbers are hexadecimal when prefixed with `0x` and decimal otherwise.
+fn parse_number(text: &str) -> Result<u16, String> {
+    match text.strip_prefix("0x") {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => text.parse(),
+    }
+    .map_err(|_| format!("Invalid number '{}'.", text))
+}
+
+/// Pauses the emulator at breakpoints and runs the command prompt.
+pub struct Debugger {
+    breakpoints: Vec<u16>,
+    opcode_breakpoints: Vec<OpcodePattern>,
+    /// Instructions left to run before pausing, or `None` to run freely.
+    steps_remaining: Option<u32>,
+    /// Set when resuming so the breakpoint we stopped on doesn't fire again straight away.
+    just_resumed: bool,
+}
+
+impl Debugger {
+    /// Creates a debugger that pauses before the first instruction.
+    pub fn new() -> Debugger {
+        Debugger {
+            breakpoints: Vec::new(),
+            opcode_breakpoints: Vec::new(),
+            steps_remaining: Some(0),
+            just_resumed: false,
+        }
+    }
+
+    /// Pauses before the next instruction.
+    pub fn pause(&mut self) {
+        self.steps_remaining = Some(0);
+    }
+
+    /// Called before every cycle to decide whether to stop at the prompt.
+    pub fn should_pause<H: Host>(&mut self, emulator: &Chip8<H>) -> bool {
+        if self.just_resumed {
+            self.just_resumed = false;
+        } else if self.is_at_breakpoint(emulator) {
+            return true;
+        }
+
+        match self.steps_remaining {
+            Some(0) => true,
+            Some(count) => {
+                self.steps_remaining = Some(count - 1);
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn is_at_breakpoint<H: Host>(&self, emulator: &Chip8<H>) -> bool {
+        let pc = emulator.pc();
+        if self.breakpoints.contains(&pc) {
+            return true;
+        }
+        match opcode_at(emulator, pc) {
+            Some(opcode) => self
+                .opcode_breakpoints
+                .iter()
+                .any(|pattern| pattern.matches(opcode)),
+            None => false,
+        }
+    }
+
+    /// Prints the watchpoints hit by the last instruction, pausing if one of
+    /// them was set to break.
+    pub fn report_watchpoints<H: Host>(&mut self, emulator: &Chip8<H>, outcome: StepOutcome) {
+        for hit in emulator.watchpoints().hits() {
+            println!("{}", describe_hit(hit));
+        }
+        if outcome == StepOutcome::HitWatchpoint {
+            self.pause();
+        }
+    }
+
+    /// Runs a frame like `Rewind::run_frame`, but one instruction at a time so
+    /// that it can stop at the prompt between them.
+    pub fn run_frame<H: Host>(
+        &mut self,
+        machine: &mut Rewind<H>,
+        cycles: u16,
+        mut refresh: impl FnMut(&mut Chip8<H>),
+    ) -> Result<Resume, ExecutionError> {
+        for _ in 0..cycles {
+            if self.should_pause(machine.emulator())
+                && self.prompt(machine, &mut refresh) == Resume::Quit
+            {
+                return Ok(Resume::Quit);
+            }
+            let outcome = machine.step()?;
+            self.report_watchpoints(machine.emulator(), outcome);
+            match outcome {
+                StepOutcome::Executed => {}
+                StepOutcome::Exited => return Ok(Resume::Quit),
+                _ => break,
+            }
+        }
+        machine.tick_60hz();
+        Ok(Resume::Run)
+    }
+
+    /// Reads and runs commands until one of them resumes or quits the emulator.
+    /// `refresh` is called whenever the display may need bringing up to date.
+    pub fn prompt<H: Host>(
+        &mut self,
+        machine: &mut Rewind<H>,
+        refresh: &mut impl FnMut(&mut Chip8<H>),
+    ) -> Resume {
+        refresh(machine.emulator_mut());
+        println!(
+            "Paused at {}",
+            describe_instruction(machine.emulator(), machine.emulator().pc())
+        );
+
+        let stdin = io::stdin();
+        loop {
+            print!("(chip8) ");
+            io::stdout().flush().expect("To flush the prompt.");
+
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+                return Resume::Quit;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let emulator = machine.emulator_mut();
+            match parse_command(&line) {
+                Ok(Command::Step(count)) => return self.resume(Some(count)),
+                Ok(Command::Back(count)) => {
+                    let undone = machine.step_back(count);
+                    refresh(machine.emulator_mut());
+                    if undone < count {
+                        println!("Reached the start of the rewind history");
+                    }
+                    let emulator = machine.emulator();
+                    println!("Back at {}", describe_instruction(emulator, emulator.pc()));
+                }
+                Ok(Command::Continue) => return self.resume(None),
+                Ok(Command::Break(address)) => {
+                    self.breakpoints.push(address);
+                    println!("Breakpoint {} at {:#06X}", self.breakpoints.len(), address);
+                }
+                Ok(Command::BreakOpcode(pattern)) => {
+                    self.opcode_breakpoints.push(pattern);
+                    println!("Breaking on opcode {}", pattern);
+                }
+                Ok(Command::Watch(watchpoint)) => {
+                    match emulator.watchpoints_mut().add(watchpoint) {
+                        Ok(index) => println!("Watchpoint {}", index),
+                        Err(error) => println!("{}", error),
+                    }
+                }
+                Ok(Command::Unwatch(index)) => match emulator.watchpoints_mut().remove(index) {
+                    Some(_) => println!("Removed watchpoint {}", index),
+                    None => println!("No watchpoint {}", index),
+                },
+                Ok(Command::Delete) => {
+                    self.breakpoints.clear();
+                    self.opcode_breakpoints.clear();
+                    emulator.watchpoints_mut().clear();
+                    println!("Deleted all breakpoints and watchpoints");
+                }
+                Ok(Command::Registers) => print_registers(emulator),
+                Ok(Command::Memory { start, length }) => print_memory(emulator, start, length),
+                Ok(Command::Disassemble(count)) => print_disassembly(emulator, count),
+                Ok(Command::Help) => println!("{}", HELP),
+                Ok(Command::Quit) => return Resume::Quit,
+                Err(error) => println!("{}", error),
+            }
+        }
+    }
+
+    fn resume(&mut self, steps: Option<u32>) -> Resume {
+        // The next instruction runs straight after the prompt, so it isn't counted.
+        self.steps_remaining = steps.map(|count| count.saturating_sub(1));
+        self.just_resumed = true;
+        Resume::Run
+    }
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Debugger::new()
+    }
+}
+
+fn opcode_at<H: Host>(emulator: &Chip8<H>, address: u16) -> Option<u16> {
+    let memory = emulator.memory();
+    let address = address as usize;
+    Some((*memory.get(address)? as u16) << 8 | *memory.get(address + 1)? as u16)
+}
+
+/// Formats the instruction at `address` as `ADDR  OPCODE  mnemonic`.
+fn describe_instruction<H: Host>(emulator: &Chip8<H>, address: u16) -> String {
+    match opcode_at(emulator, address) {
+        Some(opcode) => match Instruction::decode(opcode) {
+            Ok(Instruction::SetLongI) => format!(
+                "{:04X}  {:04X}  i := long {:#06X}",
+                address,
+                opcode,
+                opcode_at(emulator, address.wrapping_add(2)).unwrap_or(0)
+            ),
+            Ok(instruction) => format!("{:04X}  {:04X}  {}", address, opcode, instruction),
+            Err(_) => format!("{:04X}  {:04X}  ???", address, opcode),
+        },
+        None => format!("{:04X}  ----", address),
+    }
+}
+
+fn describe_hit(hit: &WatchpointHit) -> String {
+    let event = match hit.event {
+        Event::Read { address } => format!("read {:#06X}", address),
+        Event::Write { address, value } => format!("wrote {:#04X} to {:#06X}", value, address),
+        Event::Change { old, new } => format!("changed from {:#X} to {:#X}", old, new),
+    };
+    format!("Watchpoint {} at {:04X}: {}", hit.index, hit.pc, event)
+}
+
+fn print_registers<H: Host>(emulator: &Chip8<H>) {
+    println!(
+        "PC {:04X}  I {:04X}  SP {:X}  DT {:02X}  ST {:02X}",
+        emulator.pc(),
+        emulator.i(),
+        emulator.stack().len(),
+        emulator.delay_timer(),
+        emulator.sound_timer()
+    );
+    for (row_index, values) in emulator.registers().chunks(8).enumerate() {
+        let line = values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| format!("V{:X} {:02X}", row_index * 8 + index, value))
+            .collect::<Vec<String>>()
+            .join("  ");
+        println!("{}", line);
+    }
+    let stack = emulator
+        .stack()
+        .iter()
+        .map(|address| format!("{:04X}", address))
+        .collect::<Vec<String>>()
+        .join(" ");
+    println!("Stack [{}]", stack);
+}
+
+fn print_memory<H: Host>(emulator: &Chip8<H>, start: u16, length: u16) {
+    let memory = emulator.memory();
+    let start = start as usize;
+    let end = usize::min(start + length as usize, memory.len());
+    for line_start in (start..end).step_by(HEXDUMP_WIDTH) {
+        let line_end = usize::min(line_start + HEXDUMP_WIDTH, end);
+        let bytes = memory[line_start..line_end]
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<String>>()
+            .join(" ");
+        println!("{:04X}  {}", line_start, bytes);
+    }
+}
+
+/// Instructions before the PC are assumed to be 2 bytes long, since there's
+/// no way to tell where a 4 byte instruction started when reading backwards.
+fn print_disassembly<H: Host>(emulator: &Chip8<H>, count: u16) {
+    let pc = emulator.pc();
+    let mut address = pc.saturating_sub(count.saturating_mul(2));
+    for _ in 0..count.saturating_mul(2).saturating_add(1) {
+        let marker = if address == pc { '>' } else { ' ' };
+        println!("{} {}", marker, describe_instruction(emulator, address));
+
+        let size = opcode_at(emulator, address)
+            .and_then(|opcode| Instruction::decode(opcode).ok())
+            .map_or(2, |instruction| instruction.size());
+        address = match address.checked_add(size) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+}
+
+#[cfg(test)]
+#[path = "./debugger_test.rs"]
+mod debugger_test;
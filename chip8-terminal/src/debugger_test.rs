@@ -0,0 +1,105 @@
+use super::{parse_command, Command, OpcodePattern};
+use chip8_core::watchpoint::{Action, Condition, Predicate, Register, Watchpoint};
+
+#[test]
+fn parses_commands() {
+    assert_eq!(parse_command("step"), Ok(Command::Step(1)));
+    assert_eq!(parse_command("s 20\n"), Ok(Command::Step(20)));
+    assert_eq!(parse_command("back"), Ok(Command::Back(1)));
+    assert_eq!(parse_command("back 10"), Ok(Command::Back(10)));
+    assert_eq!(parse_command("c"), Ok(Command::Continue));
+    assert_eq!(parse_command("break 0x2A0"), Ok(Command::Break(0x2A0)));
+    assert_eq!(parse_command("b 512"), Ok(Command::Break(0x200)));
+    assert_eq!(
+        parse_command("memory 0x300"),
+        Ok(Command::Memory {
+            start: 0x300,
+            length: 64
+        })
+    );
+    assert_eq!(
+        parse_command("m 0x300 0x10"),
+        Ok(Command::Memory {
+            start: 0x300,
+            length: 16
+        })
+    );
+    assert_eq!(parse_command("d"), Ok(Command::Disassemble(5)));
+    assert_eq!(parse_command("registers"), Ok(Command::Registers));
+    assert_eq!(parse_command("quit"), Ok(Command::Quit));
+}
+
+#[test]
+fn rejects_invalid_commands() {
+    assert!(parse_command("jump").is_err());
+    assert!(parse_command("break").is_err());
+    assert!(parse_command("break 0xZZZ").is_err());
+    assert!(parse_command("step -1").is_err());
+    assert!(parse_command("break-opcode D12").is_err());
+}
+
+#[test]
+fn matches_opcode_patterns() {
+    let draw = OpcodePattern::parse("DXYN").unwrap();
+    assert!(draw.matches(0xD125));
+    assert!(!draw.matches(0xA125));
+    assert_eq!(draw.to_string(), "D???");
+
+    let scroll = OpcodePattern::parse("00c?").unwrap();
+    assert!(scroll.matches(0x00C4));
+    assert!(!scroll.matches(0x00E0));
+    assert_eq!(scroll.to_string(), "00C?");
+
+    assert_eq!(
+        parse_command("break-opcode 00E0"),
+        Ok(Command::BreakOpcode(OpcodePattern::parse("00E0").unwrap()))
+    );
+}
+
+#[test]
+fn parses_watchpoints() {
+    assert_eq!(
+        parse_command("watch write 0x300 0x302"),
+        Ok(Command::Watch(Watchpoint {
+            condition: Condition::Write {
+                start: 0x300,
+                end: 0x302
+            },
+            action: Action::Break,
+        }))
+    );
+    assert_eq!(
+        parse_command("log read 0x300"),
+        Ok(Command::Watch(Watchpoint {
+            condition: Condition::Read {
+                start: 0x300,
+                end: 0x300
+            },
+            action: Action::Log,
+        }))
+    );
+    assert_eq!(
+        parse_command("watch vA > 9"),
+        Ok(Command::Watch(Watchpoint {
+            condition: Condition::Change {
+                register: Register::V(10),
+                predicate: Predicate::GreaterThan(9)
+            },
+            action: Action::Break,
+        }))
+    );
+    assert_eq!(
+        parse_command("watch i"),
+        Ok(Command::Watch(Watchpoint {
+            condition: Condition::Change {
+                register: Register::I,
+                predicate: Predicate::Any
+            },
+            action: Action::Break,
+        }))
+    );
+    assert_eq!(parse_command("unwatch 2"), Ok(Command::Unwatch(2)));
+    assert!(parse_command("watch vG").is_err());
+    assert!(parse_command("watch v1 >= 3").is_err());
+    assert!(parse_command("watch").is_err());
+}
@@ -0,0 +1,98 @@
+use std::{
+    io::stdout,
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    event::{
+        KeyEventKind, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+        PushKeyboardEnhancementFlags,
+    },
+    terminal, ExecutableCommand,
+};
+
+/// How long a key stays held after a press when the terminal doesn't report
+/// releases. Auto-repeat refreshes it while the key is held down.
+pub const DEFAULT_KEY_HOLD: Duration = Duration::from_millis(150);
+
+/// Tracks which CHIP-8 keys are held down. Where the terminal reports key
+/// releases, a key is held from its press until its release. Elsewhere only
+/// presses and their auto-repeats arrive, so a key counts as held until
+/// `hold` passes without one.
+pub struct Keyboard {
+    reports_releases: bool,
+    /// Whether the terminal supports being asked to report releases.
+    can_enhance: bool,
+    /// Whether the terminal has been asked to, and needs telling to stop.
+    enhanced: bool,
+    hold: Duration,
+    pressed_at: [Option<Instant>; 16],
+}
+
+impl Keyboard {
+    /// Asks the terminal to report key releases if it can. Windows always
+    /// reports them.
+    pub fn new(hold: Duration) -> Keyboard {
+        let mut keyboard = Keyboard {
+            reports_releases: cfg!(windows),
+            can_enhance: !cfg!(windows)
+                && terminal::supports_keyboard_enhancement().unwrap_or(false),
+            enhanced: false,
+            hold,
+            pressed_at: [None; 16],
+        };
+        keyboard.resume();
+        keyboard
+    }
+
+    pub fn handle(&mut self, key: usize, kind: KeyEventKind, now: Instant) {
+        self.pressed_at[key] = match kind {
+            KeyEventKind::Release => None,
+            KeyEventKind::Press | KeyEventKind::Repeat => Some(now),
+        };
+    }
+
+    pub fn held(&self, now: Instant) -> [bool; 16] {
+        self.pressed_at.map(|pressed_at| match pressed_at {
+            Some(_) if self.reports_releases => true,
+            Some(time) => now.duration_since(time) < self.hold,
+            None => false,
+        })
+    }
+
+    /// Stops release reports, which would garble line-based input such as the
+    /// debugger prompt. Every key is let go, since its release may be missed.
+    pub fn suspend(&mut self) {
+        self.pressed_at = [None; 16];
+        if self.enhanced {
+            stdout()
+                .execute(PopKeyboardEnhancementFlags)
+                .expect("To stop reporting key releases.");
+            self.enhanced = false;
+            self.reports_releases = false;
+        }
+    }
+
+    /// Undoes `suspend`.
+    pub fn resume(&mut self) {
+        if self.can_enhance && !self.enhanced {
+            stdout()
+                .execute(PushKeyboardEnhancementFlags(
+                    KeyboardEnhancementFlags::REPORT_EVENT_TYPES,
+                ))
+                .expect("To report key releases.");
+            self.enhanced = true;
+            self.reports_releases = true;
+        }
+    }
+}
+
+impl Drop for Keyboard {
+    fn drop(&mut self) {
+        self.suspend();
+    }
+}
+
+#[cfg(test)]
+#[path = "./keyboard_test.rs"]
+mod keyboard_test;
@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+
+use crossterm::event::KeyEventKind;
+
+use super::Keyboard;
+
+fn get_keyboard(reports_releases: bool) -> Keyboard {
+    Keyboard {
+        reports_releases,
+        can_enhance: false,
+        enhanced: false,
+        hold: Duration::from_millis(100),
+        pressed_at: [None; 16],
+    }
+}
+
+#[test]
+fn holds_keys_until_released() {
+    let mut keyboard = get_keyboard(true);
+    let start = Instant::now();
+    keyboard.handle(4, KeyEventKind::Press, start);
+    assert!(keyboard.held(start + Duration::from_secs(5))[4]);
+    keyboard.handle(4, KeyEventKind::Release, start + Duration::from_secs(5));
+    assert_eq!(keyboard.held(start + Duration::from_secs(5)), [false; 16]);
+}
+
+#[test]
+fn holds_keys_until_timeout_without_releases() {
+    let mut keyboard = get_keyboard(false);
+    let start = Instant::now();
+    keyboard.handle(4, KeyEventKind::Press, start);
+    assert!(keyboard.held(start + Duration::from_millis(50))[4]);
+    // Auto-repeat keeps the key held.
+    keyboard.handle(4, KeyEventKind::Press, start + Duration::from_millis(80));
+    assert!(keyboard.held(start + Duration::from_millis(150))[4]);
+    assert!(!keyboard.held(start + Duration::from_millis(180))[4]);
+}
+
+#[test]
+fn suspending_lets_go_of_keys() {
+    let mut keyboard = get_keyboard(true);
+    let start = Instant::now();
+    keyboard.handle(0xF, KeyEventKind::Repeat, start);
+    keyboard.suspend();
+    assert_eq!(keyboard.held(start), [false; 16]);
+}
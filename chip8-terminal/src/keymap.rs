@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+
+/// The keyboard layouts that `layout` and `--keymap` accept, each listing the
+/// host keys for CHIP-8 keys 0 to F. They all use the same physical keys: the
+/// left four columns of the top four rows.
+const LAYOUTS: [(&str, &str); 5] = [
+    ("qwerty", "1234qwerasdfzxcv"),
+    ("qwertz", "1234qwerasdfyxcv"),
+    ("azerty", "&\u{e9}\"'azerqsdfwxcv"),
+    ("dvorak", "1234',.paoeu;qjk"),
+    ("colemak", "1234qwfparstzxcv"),
+];
+
+/// Host keys that control the emulator, and what they do. They're handled
+/// before the keymap, so keymap files can't bind them.
+const RESERVED_KEYS: [(char, &str); 4] = [
+    ('-', "slowing down"),
+    ('=', "speeding up"),
+    ('[', "slow motion"),
+    (']', "fast forward"),
+];
+
+/// Which host keys press which CHIP-8 keys. Keymap files are applied on top
+/// of each other and look like:
+///
+/// ```text
+/// # Start over from a preset layout.
+/// layout = azerty
+/// # Each CHIP-8 key can have several host keys, including arrows.
+/// 5 = z up
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    keys: HashMap<KeyCode, usize>,
+}
+
+impl Keymap {
+    /// One of the layouts in `LAYOUTS`.
+    pub fn from_layout(name: &str) -> Option<Keymap> {
+        let (_, layout) = LAYOUTS
+            .iter()
+            .find(|(layout, _)| layout.eq_ignore_ascii_case(name))?;
+        let keys = layout
+            .chars()
+            .enumerate()
+            .map(|(index, key)| (KeyCode::Char(key), index))
+            .collect();
+        Some(Keymap { keys })
+    }
+
+    /// Applies the lines of a keymap file. A CHIP-8 key that's mentioned loses
+    /// the host keys it had before. The keymap is left untouched on an error.
+    pub fn apply(&mut self, source: &str) -> Result<(), String> {
+        let mut keymap = self.clone();
+        for (index, line) in source.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("");
+            if line.trim().is_empty() {
+                continue;
+            }
+            keymap
+                .apply_line(line)
+                .map_err(|error| format!("Line {}: {}", index + 1, error))?;
+        }
+        *self = keymap;
+        Ok(())
+    }
+
+    fn apply_line(&mut self, line: &str) -> Result<(), String> {
+        let (name, value) = line
+            .split_once('=')
+            .ok_or("Expected 'layout = name' or 'key = host keys'.")?;
+        let (name, value) = (name.trim(), value.trim());
+        if name == "layout" {
+            *self = Keymap::from_layout(value).ok_or_else(|| unknown_layout(value))?;
+            return Ok(());
+        }
+
+        let key = u8::from_str_radix(name, 16)
+            .ok()
+            .filter(|key| *key < 16)
+            .ok_or(format!("Invalid key '{}'. Expected 0-F.", name))? as usize;
+        let host_keys = value
+            .split_whitespace()
+            .map(|name| parse_host_key(name).ok_or(format!("Unknown host key '{}'.", name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if host_keys.is_empty() {
+            return Err(format!("Expected host keys for key {:X}.", key));
+        }
+        if let Some((reserved, action)) = RESERVED_KEYS
+            .iter()
+            .find(|(reserved, _)| host_keys.contains(&KeyCode::Char(*reserved)))
+        {
+            return Err(format!(
+                "Host key '{}' is reserved for {} and can't press key {:X}.",
+                reserved, action, key
+            ));
+        }
+
+        self.keys.retain(|_, index| *index != key);
+        for host_key in host_keys {
+            self.keys.insert(host_key, key);
+        }
+        Ok(())
+    }
+
+    /// The CHIP-8 key pressed by `code`.
+    pub fn get(&self, code: &KeyCode) -> Option<usize> {
+        self.keys.get(code).copied()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::from_layout("qwerty").unwrap()
+    }
+}
+
+/// The names of the layouts, separated by commas.
+pub fn layout_names() -> String {
+    let names: Vec<&str> = LAYOUTS.iter().map(|(name, _)| *name).collect();
+    names.join(", ")
+}
+
+fn unknown_layout(name: &str) -> String {
+    format!(
+        "Unknown keyboard layout '{}'. Expected one of: {}.",
+        name,
+        layout_names()
+    )
+}
+
+/// A single character, or the name of a key such as `up` or `space`.
+fn parse_host_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(key), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(key.to_ascii_lowercase()));
+    }
+    let key = match name.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "tab" => KeyCode::Tab,
+        "insert" => KeyCode::Insert,
+        "delete" => KeyCode::Delete,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+#[path = "./keymap_test.rs"]
+mod keymap_test;
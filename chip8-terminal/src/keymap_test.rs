@@ -0,0 +1,93 @@
+use crossterm::event::KeyCode;
+
+use super::Keymap;
+
+#[test]
+fn defaults_to_qwerty() {
+    let keymap = Keymap::default();
+    assert_eq!(keymap.get(&KeyCode::Char('1')), Some(0));
+    assert_eq!(keymap.get(&KeyCode::Char('w')), Some(5));
+    assert_eq!(keymap.get(&KeyCode::Char('v')), Some(15));
+    assert_eq!(keymap.get(&KeyCode::Char('5')), None);
+}
+
+#[test]
+fn loads_layouts() {
+    let keymap = Keymap::from_layout("AZERTY").unwrap();
+    assert_eq!(keymap.get(&KeyCode::Char('\u{e9}')), Some(1));
+    assert_eq!(keymap.get(&KeyCode::Char('z')), Some(5));
+    assert_eq!(keymap.get(&KeyCode::Char('w')), Some(12));
+    assert_eq!(keymap, {
+        let mut keymap = Keymap::default();
+        keymap.apply("layout = azerty").unwrap();
+        keymap
+    });
+    assert_eq!(Keymap::from_layout("bepo"), None);
+}
+
+#[test]
+fn maps_several_host_keys() {
+    let mut keymap = Keymap::default();
+    keymap
+        .apply("# Arrows move\n5 = w up  # and W\n8 = S Down\n\n")
+        .unwrap();
+    assert_eq!(keymap.get(&KeyCode::Char('w')), Some(5));
+    assert_eq!(keymap.get(&KeyCode::Up), Some(5));
+    assert_eq!(keymap.get(&KeyCode::Char('s')), Some(8));
+    assert_eq!(keymap.get(&KeyCode::Down), Some(8));
+    // S used to press 9 and A 8.
+    assert_eq!(keymap.get(&KeyCode::Char('a')), None);
+    assert_eq!(keymap.get(&KeyCode::Char('d')), Some(10));
+}
+
+#[test]
+fn later_lines_take_host_keys() {
+    let mut keymap = Keymap::default();
+    keymap.apply("1 = space\n2 = space").unwrap();
+    assert_eq!(keymap.get(&KeyCode::Char(' ')), Some(2));
+    assert_eq!(keymap.get(&KeyCode::Char('2')), None);
+}
+
+#[test]
+fn rejects_reserved_host_keys() {
+    let mut keymap = Keymap::default();
+    assert_eq!(
+        keymap.apply("5 = w\n8 = s ]"),
+        Err("Line 2: Host key ']' is reserved for fast forward and can't press key 8.".to_string())
+    );
+    assert_eq!(
+        keymap.apply("0 = -"),
+        Err("Line 1: Host key '-' is reserved for slowing down and can't press key 0.".to_string())
+    );
+    assert_eq!(keymap, Keymap::default());
+}
+
+#[test]
+fn rejects_invalid_lines() {
+    let mut keymap = Keymap::default();
+    assert_eq!(
+        keymap.apply("5 = up\nG = w"),
+        Err("Line 2: Invalid key 'G'. Expected 0-F.".to_string())
+    );
+    assert_eq!(
+        keymap.apply("5 = shift"),
+        Err("Line 1: Unknown host key 'shift'.".to_string())
+    );
+    assert_eq!(
+        keymap.apply("5 ="),
+        Err("Line 1: Expected host keys for key 5.".to_string())
+    );
+    assert_eq!(
+        keymap.apply("5 w"),
+        Err("Line 1: Expected 'layout = name' or 'key = host keys'.".to_string())
+    );
+    assert_eq!(
+        keymap.apply("layout = bepo"),
+        Err(
+            "Line 1: Unknown keyboard layout 'bepo'. Expected one of: qwerty, qwertz, azerty, \
+             dvorak, colemak."
+                .to_string()
+        )
+    );
+    assert_eq!(keymap, Keymap::default());
+}
@@ -0,0 +1,590 @@
+use core::time;
+use std::{
+    env, fs,
+    io::stdout,
+    path::{Path, PathBuf},
+    process,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use audio_output::{AudioOutput, Sound};
+use chip8_core::{
+    audio::AudioState,
+    disassembler,
+    emulator::{Chip8, StepOutcome, TIMER_CLOCK},
+    host::Host,
+    quirks::Quirks,
+    random::SplitMix64,
+    rewind::Rewind,
+};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    terminal::{self, ClearType},
+    ExecutableCommand,
+};
+use debugger::{Debugger, Resume};
+use keyboard::Keyboard;
+use keymap::Keymap;
+use render::{Brightness, Renderer};
+use screen::Screen;
+use theme::{ColorMode, Rgb, Theme};
+
+mod audio_output;
+mod debugger;
+mod keyboard;
+mod keymap;
+mod render;
+mod screen;
+mod theme;
+
+const DEFAULT_INSTRUCTIONS_PER_FRAME: u16 = 8;
+const MAX_INSTRUCTIONS_PER_FRAME: u16 = 1000;
+/// Frames run for every frame shown when fast-forwarding.
+const FAST_FORWARD_FRAMES: u32 = 4;
+/// How many times longer each frame takes in slow motion.
+const SLOW_MOTION_FACTOR: u32 = 4;
+/// F1-F4 save to these slots and F5-F8 load from them.
+const SAVE_STATE_SLOTS: u8 = 4;
+const DEFAULT_REWIND_SECONDS: u32 = 10;
+/// Each repeat of the rewind key undoes this fraction of a second.
+const REWIND_STEPS_PER_SECOND: u32 = 10;
+/// The display is drawn under the header.
+const DISPLAY_TOP: u16 = 1;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(2);
+        }
+    };
+    let rom_path = Path::new(&options.rom_path);
+    let data = fs::read(rom_path).expect("Unable to read file");
+
+    if options.disassemble {
+        print!("{}", disassembler::disassemble(&data));
+        return;
+    }
+
+    let keymap = load_keymap(rom_path, options.keymap.as_deref()).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(2);
+    });
+
+    stdout()
+        .execute(terminal::Clear(ClearType::All))
+        .unwrap()
+        .execute(cursor::MoveTo(0, 0))
+        .unwrap();
+
+    let rom_name = rom_path.file_name().unwrap().to_str().unwrap();
+    print_header(rom_name, "");
+    let mut screen = Screen::new(
+        DISPLAY_TOP,
+        options.renderer,
+        options.theme,
+        options.color_mode,
+        Brightness::new(options.fade),
+    );
+
+    // Without a seed, every run plays out differently.
+    let rng = options
+        .seed
+        .map_or_else(SplitMix64::from_system_time, SplitMix64::new);
+    let (_stream, stream_handle) = rodio::OutputStream::try_default().unwrap();
+    let sink = rodio::Sink::try_new(&stream_handle).unwrap();
+    let sound = Arc::new(Mutex::new(Sound {
+        audio: AudioState::new(),
+        is_playing: false,
+    }));
+    sink.append(AudioOutput::new(Arc::clone(&sound)));
+
+    let host = TerminalHost {
+        keys: [false; 16],
+        sound,
+    };
+    let mut emulator = Chip8::with_host(host, options.quirks, rng);
+    if let Err(error) = emulator.load(&data) {
+        panic!("Error loading data: {}", error);
+    }
+    let mut machine = Rewind::new(emulator, options.rewind_seconds.saturating_mul(TIMER_CLOCK));
+
+    let frame_duration = time::Duration::from_secs(1) / TIMER_CLOCK;
+    let mut instructions_per_frame = DEFAULT_INSTRUCTIONS_PER_FRAME;
+    let mut speed = Speed::Normal;
+
+    let mut debugger = options.debug.then(Debugger::new);
+    let mut keyboard = Keyboard::new(options.key_hold);
+
+    'running: loop {
+        let frame_start = Instant::now();
+        machine.emulator_mut().host_mut().keys = keyboard.held(frame_start);
+        let frames = if speed == Speed::FastForward {
+            FAST_FORWARD_FRAMES
+        } else {
+            1
+        };
+        for _ in 0..frames {
+            let result = match &mut debugger {
+                Some(debugger) => {
+                    // The prompt reads whole lines, which key releases would garble.
+                    let result =
+                        debugger.run_frame(&mut machine, instructions_per_frame, |emulator| {
+                            keyboard.suspend();
+                            emulator.host_mut().keys = [false; 16];
+                            screen.invalidate();
+                            draw_if_dirty(emulator, &mut screen);
+                        });
+                    keyboard.resume();
+                    result
+                }
+                None => machine.run_frame(instructions_per_frame).map(|frame| {
+                    if frame.outcome == StepOutcome::Exited {
+                        Resume::Quit
+                    } else {
+                        Resume::Run
+                    }
+                }),
+            };
+            match result {
+                Ok(Resume::Run) => {}
+                Ok(Resume::Quit) => break 'running,
+                Err(error) => {
+                    terminal::disable_raw_mode().expect("To disable raw mode.");
+                    eprintln!("Emulation stopped: {}", error);
+                    match &mut debugger {
+                        // Leave the faulting state around to be inspected.
+                        Some(debugger) => debugger.pause(),
+                        None => {
+                            drop(keyboard);
+                            process::exit(1);
+                        }
+                    }
+                }
+            }
+        }
+
+        let emulator = machine.emulator_mut();
+        if debugger.is_some() {
+            // Anything the debugger printed may have scrolled the display.
+            screen.invalidate();
+        }
+        draw_if_dirty(emulator, &mut screen);
+
+        // Wait out the rest of the frame, which is stretched when in slow motion.
+        let frame_duration = match speed {
+            Speed::SlowMotion => frame_duration * SLOW_MOTION_FACTOR,
+            _ => frame_duration,
+        };
+        match poll_for_keyboard_input(
+            &mut keyboard,
+            &keymap,
+            frame_duration.saturating_sub(frame_start.elapsed()),
+        ) {
+            Ok(()) => {}
+            Err(Interrupt::Pause) => {
+                if let Some(debugger) = &mut debugger {
+                    debugger.pause();
+                }
+            }
+            Err(Interrupt::SaveState(slot)) => {
+                let path = save_state_path(rom_path, slot);
+                let status = match fs::write(&path, emulator.save_state()) {
+                    Ok(()) => format!("Saved state {}", slot),
+                    Err(error) => format!("Unable to save state {}: {}", slot, error),
+                };
+                print_header(rom_name, &status);
+            }
+            Err(Interrupt::LoadState(slot)) => {
+                let path = save_state_path(rom_path, slot);
+                let status = match fs::read(&path) {
+                    Ok(state) => match emulator.load_state(&state) {
+                        Ok(()) => format!("Loaded state {}", slot),
+                        Err(error) => format!("Unable to load state {}: {}", slot, error),
+                    },
+                    Err(error) => format!("Unable to load state {}: {}", slot, error),
+                };
+                print_header(rom_name, &status);
+            }
+            Err(Interrupt::Rewind) => {
+                machine.step_back(rewind_step(instructions_per_frame));
+            }
+            Err(Interrupt::ChangeInstructionsPerFrame(change)) => {
+                instructions_per_frame = u16::clamp(
+                    instructions_per_frame.saturating_add_signed(change),
+                    1,
+                    MAX_INSTRUCTIONS_PER_FRAME,
+                );
+                print_header(
+                    rom_name,
+                    &format!("{} instructions per frame", instructions_per_frame),
+                );
+            }
+            Err(Interrupt::ToggleSpeed(toggled)) => {
+                speed = if speed == toggled {
+                    Speed::Normal
+                } else {
+                    toggled
+                };
+                print_header(rom_name, speed.status());
+            }
+            Err(Interrupt::Resize) => {
+                screen.invalidate();
+                screen
+                    .draw(emulator.display())
+                    .expect("To draw the display.");
+            }
+            Err(Interrupt::Quit) => break,
+        }
+    }
+}
+
+/// Starts from the QWERTY layout and applies the global keymap file, the ROM's
+/// keymap file, e.g. `pong.keymap`, and then `--keymap`, each on top of the
+/// last. `--keymap` is either the path to a file or the name of a layout.
+fn load_keymap(rom_path: &Path, option: Option<&str>) -> Result<Keymap, String> {
+    let mut keymap = Keymap::default();
+    for path in [
+        global_keymap_path(),
+        Some(rom_path.with_extension("keymap")),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if let Ok(source) = fs::read_to_string(&path) {
+            keymap
+                .apply(&source)
+                .map_err(|error| format!("{}: {}", path.display(), error))?;
+        }
+    }
+
+    if let Some(option) = option {
+        match Keymap::from_layout(option) {
+            Some(layout) => keymap = layout,
+            None => {
+                let source = fs::read_to_string(option).map_err(|error| {
+                    format!(
+                        "Unable to read keymap '{}': {}. Expected a file or one of these layouts: {}.",
+                        option,
+                        error,
+                        keymap::layout_names()
+                    )
+                })?;
+                keymap
+                    .apply(&source)
+                    .map_err(|error| format!("{}: {}", option, error))?;
+            }
+        }
+    }
+    Ok(keymap)
+}
+
+/// `chip-8-rust/default.keymap` in the user's configuration directory.
+fn global_keymap_path() -> Option<PathBuf> {
+    let config = if cfg!(windows) {
+        PathBuf::from(env::var_os("APPDATA")?)
+    } else {
+        match env::var_os("XDG_CONFIG_HOME") {
+            Some(config) => PathBuf::from(config),
+            None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        }
+    };
+    Some(config.join("chip-8-rust").join("default.keymap"))
+}
+
+/// Save states for each slot are kept next to the ROM, e.g. `pong.state1`.
+fn save_state_path(rom_path: &Path, slot: u8) -> PathBuf {
+    rom_path.with_extension(format!("state{}", slot))
+}
+
+struct Options {
+    rom_path: String,
+    quirks: Quirks,
+    disassemble: bool,
+    debug: bool,
+    rewind_seconds: u32,
+    seed: Option<u64>,
+    /// How long a key press lasts when the terminal doesn't report releases.
+    key_hold: Duration,
+    keymap: Option<String>,
+    /// `None` picks a renderer to fit the terminal.
+    renderer: Option<Renderer>,
+    theme: Theme,
+    color_mode: ColorMode,
+    /// Frames that erased pixels take to fade out.
+    fade: u8,
+}
+
+/// Input that interrupts emulation rather than being passed on to the ROM.
+enum Interrupt {
+    Quit,
+    Pause,
+    SaveState(u8),
+    LoadState(u8),
+    Rewind,
+    ChangeInstructionsPerFrame(i16),
+    ToggleSpeed(Speed),
+    /// The terminal changed size, which may have garbled the display.
+    Resize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Speed {
+    Normal,
+    FastForward,
+    SlowMotion,
+}
+
+impl Speed {
+    fn status(&self) -> &'static str {
+        match self {
+            Speed::Normal => "",
+            Speed::FastForward => "Fast-forward",
+            Speed::SlowMotion => "Slow motion",
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut rom_path = None;
+    let mut quirks = Quirks::default();
+    let mut disassemble = false;
+    let mut debug = false;
+    let mut rewind_seconds = DEFAULT_REWIND_SECONDS;
+    let mut seed = None;
+    let mut key_hold = keyboard::DEFAULT_KEY_HOLD;
+    let mut keymap = None;
+    let mut renderer = None;
+    let mut theme = Theme::from_name("default").unwrap();
+    let mut foreground = None;
+    let mut background = None;
+    let mut color_mode = None;
+    let mut fade = 0;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--quirks" => {
+                let name = args
+                    .next()
+                    .ok_or("Expected a preset name after --quirks.")?;
+                quirks = Quirks::from_preset_name(name).ok_or(format!(
+                    "Unknown quirks preset '{}'. Expected one of: vip, chip48, schip, xochip.",
+                    name
+                ))?;
+            }
+            "--disassemble" => disassemble = true,
+            "--debug" => debug = true,
+            "--seed" => {
+                let value = args.next().ok_or("Expected a number after --seed.")?;
+                seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid seed '{}'.", value))?,
+                );
+            }
+            "--rewind" => {
+                let seconds = args
+                    .next()
+                    .ok_or("Expected a number of seconds after --rewind.")?;
+                rewind_seconds = seconds
+                    .parse()
+                    .map_err(|_| format!("Invalid number of seconds to rewind '{}'.", seconds))?;
+            }
+            "--key-hold" => {
+                let milliseconds = args
+                    .next()
+                    .ok_or("Expected a number of milliseconds after --key-hold.")?;
+                key_hold =
+                    Duration::from_millis(milliseconds.parse().map_err(|_| {
+                        format!("Invalid number of milliseconds '{}'.", milliseconds)
+                    })?);
+            }
+            "--keymap" => {
+                keymap = Some(
+                    args.next()
+                        .ok_or("Expected a file or layout name after --keymap.")?
+                        .clone(),
+                );
+            }
+            "--renderer" => {
+                let name = args.next().ok_or("Expected a renderer after --renderer.")?;
+                renderer = match name.as_str() {
+                    "auto" => None,
+                    _ => Some(Renderer::from_name(name).ok_or(format!(
+                        "Unknown renderer '{}'. Expected one of: auto, block, half, braille.",
+                        name
+                    ))?),
+                };
+            }
+            "--theme" => {
+                let name = args.next().ok_or("Expected a theme after --theme.")?;
+                theme = Theme::from_name(name).ok_or(format!(
+                    "Unknown theme '{}'. Expected one of: {}.",
+                    name,
+                    Theme::names()
+                ))?;
+            }
+            "--foreground" => foreground = Some(parse_color(arg, args.next())?),
+            "--background" => background = Some(parse_color(arg, args.next())?),
+            "--colors" => {
+                let name = args
+                    .next()
+                    .ok_or("Expected truecolor or 256 after --colors.")?;
+                color_mode = Some(ColorMode::from_name(name).ok_or(format!(
+                    "Unknown color mode '{}'. Expected one of: truecolor, 256.",
+                    name
+                ))?);
+            }
+            "--fade" => {
+                let frames = args
+                    .next()
+                    .ok_or("Expected a number of frames after --fade.")?;
+                fade = frames
+                    .parse()
+                    .map_err(|_| format!("Invalid number of frames to fade '{}'.", frames))?;
+            }
+            _ if rom_path.is_none() => rom_path = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument '{}'.", arg)),
+        }
+    }
+
+    Ok(Options {
+        rom_path: rom_path.ok_or("Expected a path to the ROM to open.")?,
+        quirks,
+        disassemble,
+        debug,
+        rewind_seconds,
+        seed,
+        key_hold,
+        keymap,
+        renderer,
+        // Colors given on their own replace the theme's, wherever they are.
+        theme: Theme {
+            foreground: foreground.or(theme.foreground),
+            background: background.or(theme.background),
+        },
+        color_mode: color_mode.unwrap_or_else(ColorMode::detect),
+        fade,
+    })
+}
+
+fn parse_color(option: &str, value: Option<&String>) -> Result<Rgb, String> {
+    let value = value.ok_or(format!("Expected a color after {}.", option))?;
+    Rgb::parse(value).ok_or(format!(
+        "Invalid color '{}'. Expected one like #RRGGBB.",
+        value
+    ))
+}
+
+fn poll_for_keyboard_input(
+    keyboard: &mut Keyboard,
+    keymap: &Keymap,
+    duration: Duration,
+) -> Result<(), Interrupt> {
+    // Set raw mode so we can detect input without requiring Enter to be pressed.
+    terminal::enable_raw_mode().expect("To enable raw mode.");
+
+    let start = Instant::now();
+    let mut duration_since_start = Instant::now().duration_since(start);
+
+    while duration_since_start < duration {
+        if event::poll(duration - duration_since_start).expect("Failed to poll.") {
+            match event::read().expect("Failed to read line.") {
+                Event::Key(event) => {
+                    if let Some(interrupt) = interrupt_for(&event) {
+                        terminal::disable_raw_mode().expect("To disable raw mode.");
+                        return Err(interrupt);
+                    }
+
+                    if let Some(index) = keymap.get(&event.code) {
+                        keyboard.handle(index, event.kind, Instant::now());
+                    }
+                }
+                Event::Resize(_, _) => {
+                    terminal::disable_raw_mode().expect("To disable raw mode.");
+                    return Err(Interrupt::Resize);
+                }
+                _ => {}
+            }
+        }
+
+        duration_since_start = Instant::now().duration_since(start);
+    }
+
+    terminal::disable_raw_mode().expect("To disable raw mode.");
+    Ok(())
+}
+
+/// Instructions undone each time the rewind key repeats, at the current speed.
+fn rewind_step(instructions_per_frame: u16) -> usize {
+    (instructions_per_frame as u32 * TIMER_CLOCK / REWIND_STEPS_PER_SECOND) as usize
+}
+
+/// The interrupt a key press or repeat triggers, if any.
+fn interrupt_for(event: &KeyEvent) -> Option<Interrupt> {
+    if event.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    let control = event.modifiers == event::KeyModifiers::CONTROL;
+    match event.code {
+        KeyCode::Char('c') | KeyCode::Char('z') if control => Some(Interrupt::Quit),
+        KeyCode::Char('b') if control => Some(Interrupt::Pause),
+        KeyCode::Backspace => Some(Interrupt::Rewind),
+        KeyCode::Char('-') => Some(Interrupt::ChangeInstructionsPerFrame(-1)),
+        KeyCode::Char('=') => Some(Interrupt::ChangeInstructionsPerFrame(1)),
+        KeyCode::Char(']') => Some(Interrupt::ToggleSpeed(Speed::FastForward)),
+        KeyCode::Char('[') => Some(Interrupt::ToggleSpeed(Speed::SlowMotion)),
+        KeyCode::F(number) if (1..=SAVE_STATE_SLOTS).contains(&number) => {
+            Some(Interrupt::SaveState(number))
+        }
+        KeyCode::F(number) if number <= SAVE_STATE_SLOTS * 2 => {
+            Some(Interrupt::LoadState(number - SAVE_STATE_SLOTS))
+        }
+        _ => None,
+    }
+}
+
+/// Prints the title line at the top of the terminal, followed by a status message.
+fn print_header(rom_name: &str, status: &str) {
+    stdout().execute(cursor::MoveTo(0, 0)).unwrap();
+    print!("CHIP-8   ROM: {}   {}", rom_name, status);
+    stdout()
+        .execute(terminal::Clear(ClearType::UntilNewLine))
+        .unwrap();
+}
+
+/// The keys held at the start of the frame, and the sound shared with the audio thread.
+struct TerminalHost {
+    keys: [bool; 16],
+    sound: Arc<Mutex<Sound>>,
+}
+
+impl Host for TerminalHost {
+    fn is_key_pressed(&self, key: u8) -> bool {
+        self.keys[key as usize]
+    }
+
+    fn set_sound(&mut self, sound: Option<&AudioState>) {
+        let mut shared = self.sound.lock().expect("To lock the sound state.");
+        shared.is_playing = sound.is_some();
+        if let Some(audio) = sound {
+            shared.audio = *audio;
+        }
+    }
+}
+
+/// Draws the display if it changed, or while pixels are still fading out.
+fn draw_if_dirty(emulator: &mut Chip8<TerminalHost>, screen: &mut Screen) {
+    if emulator.is_display_dirty() || screen.is_fading() {
+        screen
+            .draw(emulator.display())
+            .expect("To draw the display.");
+        emulator.clear_display_dirty();
+    }
+}
@@ -0,0 +1,194 @@
+use chip8_core::display::Display;
+
+/// The brightness of a pixel that's on.
+pub const LIT: u8 = 255;
+
+/// How pixels are drawn as terminal cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Renderer {
+    /// A `█` for each pixel.
+    Block,
+    /// Two pixels stacked in each cell, drawn with `▀`, `▄` and `█`.
+    HalfBlock,
+    /// Two columns of four pixels in each cell, drawn with Braille patterns.
+    Braille,
+}
+
+/// A character cell, with the brightness of its foreground and background
+/// from 0 to `LIT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub symbol: char,
+    pub foreground: u8,
+    pub background: u8,
+}
+
+impl Cell {
+    const EMPTY: Cell = Cell {
+        symbol: ' ',
+        foreground: 0,
+        background: 0,
+    };
+
+    fn new(symbol: char, foreground: u8) -> Cell {
+        Cell {
+            symbol,
+            foreground,
+            background: 0,
+        }
+    }
+}
+
+impl Renderer {
+    pub fn from_name(name: &str) -> Option<Renderer> {
+        match name {
+            "block" => Some(Renderer::Block),
+            "half" => Some(Renderer::HalfBlock),
+            "braille" => Some(Renderer::Braille),
+            _ => None,
+        }
+    }
+
+    /// The width and height in pixels of each cell.
+    pub fn cell_size(&self) -> (usize, usize) {
+        match self {
+            Renderer::Block => (1, 1),
+            Renderer::HalfBlock => (1, 2),
+            Renderer::Braille => (2, 4),
+        }
+    }
+
+    /// The most detailed renderer that fits a `width` by `height` display into
+    /// `columns` by `rows` cells, or Braille if none of them do.
+    pub fn fitting(width: usize, height: usize, columns: usize, rows: usize) -> Renderer {
+        [Renderer::Block, Renderer::HalfBlock]
+            .into_iter()
+            .find(|renderer| {
+                let (cell_width, cell_height) = renderer.cell_size();
+                width.div_ceil(cell_width) <= columns && height.div_ceil(cell_height) <= rows
+            })
+            .unwrap_or(Renderer::Braille)
+    }
+
+    /// Draws rows of pixel brightnesses as lines of cells.
+    pub fn render(&self, rows: &[&[u8]]) -> Vec<Vec<Cell>> {
+        let (cell_width, cell_height) = self.cell_size();
+        let width = rows.first().map_or(0, |row| row.len());
+        let brightness =
+            |x: usize, y: usize| rows.get(y).and_then(|row| row.get(x)).copied().unwrap_or(0);
+
+        (0..rows.len())
+            .step_by(cell_height)
+            .map(|y| {
+                (0..width)
+                    .step_by(cell_width)
+                    .map(|x| match self {
+                        Renderer::Block => block(brightness(x, y)),
+                        Renderer::HalfBlock => half_block(brightness(x, y), brightness(x, y + 1)),
+                        Renderer::Braille => braille(|dx, dy| brightness(x + dx, y + dy)),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn block(brightness: u8) -> Cell {
+    if brightness > 0 {
+        Cell::new('\u{2588}', brightness)
+    } else {
+        Cell::EMPTY
+    }
+}
+
+/// The upper half block takes the top pixel's brightness as its foreground
+/// and the bottom one's as its background, unless they match.
+fn half_block(top: u8, bottom: u8) -> Cell {
+    match (top, bottom) {
+        (0, 0) => Cell::EMPTY,
+        (0, _) => Cell::new('\u{2584}', bottom),
+        (_, 0) => Cell::new('\u{2580}', top),
+        _ if top == bottom => Cell::new('\u{2588}', top),
+        _ => Cell {
+            symbol: '\u{2580}',
+            foreground: top,
+            background: bottom,
+        },
+    }
+}
+
+/// The Braille pattern for a 2x4 cell, where `brightness` takes the offset of
+/// a pixel within it. The dots can't differ in brightness, so they all take
+/// the brightest one's. Empty cells are spaces, which some fonts draw
+/// narrower than a blank pattern.
+fn braille(brightness: impl Fn(usize, usize) -> u8) -> Cell {
+    // The bit for each dot, by row and then column.
+    const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+    let mut pattern = 0;
+    let mut brightest = 0;
+    for (dy, row) in DOTS.iter().enumerate() {
+        for (dx, dot) in row.iter().enumerate() {
+            let brightness = brightness(dx, dy);
+            if brightness > 0 {
+                pattern |= dot;
+                brightest = brightest.max(brightness);
+            }
+        }
+    }
+    if pattern == 0 {
+        Cell::EMPTY
+    } else {
+        Cell::new(char::from_u32(0x2800 + pattern).unwrap(), brightest)
+    }
+}
+
+/// The brightness of each pixel of the display. With a fade, pixels that are
+/// turned off dim over a few frames, like the phosphor of a CRT, which hides
+/// the flicker of sprites being erased and redrawn.
+pub struct Brightness {
+    /// Each frame, fading pixels lose this much brightness.
+    step: u8,
+    width: usize,
+    levels: Vec<u8>,
+}
+
+impl Brightness {
+    /// Pixels take `fade_frames` frames to go dark after being turned off.
+    pub fn new(fade_frames: u8) -> Brightness {
+        Brightness {
+            step: LIT.div_ceil(fade_frames.saturating_add(1)),
+            width: 0,
+            levels: Vec::new(),
+        }
+    }
+
+    /// Takes the pixels that are on from the display, and dims the rest by a
+    /// frame's worth.
+    pub fn update(&mut self, display: &Display) {
+        if self.width != display.width() || self.levels.len() != display.width() * display.height()
+        {
+            self.width = display.width();
+            self.levels = vec![0; display.width() * display.height()];
+        }
+        for (level, pixel) in self.levels.iter_mut().zip(display.rows().flatten()) {
+            *level = if *pixel != 0 {
+                LIT
+            } else {
+                level.saturating_sub(self.step)
+            };
+        }
+    }
+
+    /// Whether any pixel is still fading, and needs drawing again next frame.
+    pub fn is_fading(&self) -> bool {
+        self.levels.iter().any(|level| *level != 0 && *level != LIT)
+    }
+
+    pub fn rows(&self) -> Vec<&[u8]> {
+        self.levels.chunks(self.width.max(1)).collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "./render_test.rs"]
+mod render_test;
@@ -0,0 +1,118 @@
+use chip8_core::{
+    display::{HIRES_HEIGHT, HIRES_WIDTH, LORES_HEIGHT, LORES_WIDTH},
+    emulator::Chip8,
+    quirks::Quirks,
+};
+
+use super::{Brightness, Cell, Renderer, LIT};
+
+const ROWS: [&[u8]; 4] = [
+    &[LIT, 0, LIT, 0],
+    &[LIT, 0, 0, 100],
+    &[0, 0, 0, 0],
+    &[LIT, LIT, 0, 0],
+];
+
+fn symbols(lines: Vec<Vec<Cell>>) -> Vec<String> {
+    lines
+        .into_iter()
+        .map(|line| line.into_iter().map(|cell| cell.symbol).collect())
+        .collect()
+}
+
+#[test]
+fn renders_blocks() {
+    assert_eq!(
+        symbols(Renderer::Block.render(&ROWS)),
+        [
+            "\u{2588} \u{2588} ",
+            "\u{2588}  \u{2588}",
+            "    ",
+            "\u{2588}\u{2588}  "
+        ]
+    );
+    assert_eq!(Renderer::Block.render(&ROWS)[1][3].foreground, 100);
+}
+
+#[test]
+fn renders_half_blocks() {
+    let lines = Renderer::HalfBlock.render(&ROWS);
+    assert_eq!(
+        symbols(lines.clone()),
+        ["\u{2588} \u{2580}\u{2584}", "\u{2584}\u{2584}  "]
+    );
+    assert_eq!(
+        Renderer::HalfBlock.render(&[&[LIT], &[100]])[0][0],
+        Cell {
+            symbol: '\u{2580}',
+            foreground: LIT,
+            background: 100
+        }
+    );
+}
+
+#[test]
+fn renders_braille() {
+    // Dots 1, 2, 7 and 8, then dots 1 and 5, which take the brightest one.
+    let lines = Renderer::Braille.render(&ROWS);
+    assert_eq!(symbols(lines.clone()), ["\u{28C3}\u{2811}"]);
+    assert_eq!(lines[0][1].foreground, LIT);
+    assert_eq!(
+        symbols(Renderer::Braille.render(&[&[0, 0], &[0, 0]])),
+        [" "]
+    );
+}
+
+#[test]
+fn fits_the_terminal() {
+    assert_eq!(
+        Renderer::fitting(LORES_WIDTH, LORES_HEIGHT, 80, 40),
+        Renderer::Block
+    );
+    assert_eq!(
+        Renderer::fitting(LORES_WIDTH, LORES_HEIGHT, 80, 24),
+        Renderer::HalfBlock
+    );
+    assert_eq!(
+        Renderer::fitting(HIRES_WIDTH, HIRES_HEIGHT, 80, 24),
+        Renderer::Braille
+    );
+    assert_eq!(
+        Renderer::fitting(HIRES_WIDTH, HIRES_HEIGHT, 200, 40),
+        Renderer::HalfBlock
+    );
+}
+
+#[test]
+fn fades_erased_pixels() {
+    // Draw the top row of the 0 sprite, then clear the screen.
+    let mut chip8 = Chip8::new(Quirks::default());
+    chip8.load(&[0xD0, 0x01, 0x00, 0xE0]).unwrap();
+    let mut brightness = Brightness::new(4);
+
+    chip8.step().unwrap();
+    brightness.update(chip8.display());
+    assert_eq!(brightness.rows()[0][..5], [LIT, LIT, LIT, LIT, 0]);
+    assert!(!brightness.is_fading());
+
+    chip8.step().unwrap();
+    let mut levels = Vec::new();
+    for _ in 0..5 {
+        brightness.update(chip8.display());
+        levels.push(brightness.rows()[0][0]);
+    }
+    assert_eq!(levels, [204, 153, 102, 51, 0]);
+    assert!(!brightness.is_fading());
+}
+
+#[test]
+fn turns_pixels_off_without_a_fade() {
+    let mut chip8 = Chip8::new(Quirks::default());
+    chip8.load(&[0xD0, 0x01, 0x00, 0xE0]).unwrap();
+    let mut brightness = Brightness::new(0);
+    chip8.step().unwrap();
+    brightness.update(chip8.display());
+    chip8.step().unwrap();
+    brightness.update(chip8.display());
+    assert_eq!(brightness.rows()[0][0], 0);
+}
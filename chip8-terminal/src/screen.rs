@@ -0,0 +1,173 @@
+use std::io::{self, Write};
+
+use chip8_core::display::Display;
+use crossterm::{
+    cursor,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, ClearType},
+    QueueableCommand,
+};
+
+use crate::{
+    render::{Brightness, Renderer},
+    theme::{ColorMode, StyledCell, Theme},
+};
+
+/// Draws the display to the terminal, remembering what it drew so that only
+/// the cells that have changed since need drawing again.
+pub struct Screen {
+    /// The terminal row the display starts on. One row is left below it.
+    top: u16,
+    /// `None` picks the most detailed renderer that fits the terminal.
+    renderer: Option<Renderer>,
+    theme: Theme,
+    color_mode: ColorMode,
+    brightness: Brightness,
+    /// Empty when the next draw has to redraw everything.
+    lines: Vec<Vec<StyledCell>>,
+}
+
+/// Cells to print at a position, given as a column and line of the display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run<'a> {
+    pub x: usize,
+    pub y: usize,
+    pub cells: &'a [StyledCell],
+}
+
+impl Screen {
+    pub fn new(
+        top: u16,
+        renderer: Option<Renderer>,
+        theme: Theme,
+        color_mode: ColorMode,
+        brightness: Brightness,
+    ) -> Screen {
+        Screen {
+            top,
+            renderer,
+            theme,
+            color_mode,
+            brightness,
+            lines: Vec::new(),
+        }
+    }
+
+    /// Redraws everything next time, e.g. after other output may have
+    /// scrolled the display away.
+    pub fn invalidate(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Whether pixels are still fading out, so the display needs drawing
+    /// every frame even if it hasn't changed.
+    pub fn is_fading(&self) -> bool {
+        self.brightness.is_fading()
+    }
+
+    /// Draws a frame of the display over what was drawn before, then leaves
+    /// the cursor on the line below it. Everything is written with a single
+    /// flush.
+    pub fn draw(&mut self, display: &Display) -> io::Result<()> {
+        self.brightness.update(display);
+        let renderer = self.renderer.unwrap_or_else(|| {
+            let (columns, rows) = terminal::size().unwrap_or((u16::MAX, u16::MAX));
+            Renderer::fitting(
+                display.width(),
+                display.height(),
+                columns as usize,
+                rows.saturating_sub(self.top + 1) as usize,
+            )
+        });
+        let lines: Vec<Vec<StyledCell>> = renderer
+            .render(&self.brightness.rows())
+            .into_iter()
+            .map(|line| {
+                line.into_iter()
+                    .map(|cell| self.theme.style(cell, self.color_mode))
+                    .collect()
+            })
+            .collect();
+
+        let mut stdout = io::stdout();
+        stdout.queue(cursor::Hide)?;
+        if is_same_shape(&self.lines, &lines) {
+            for run in changed_runs(&self.lines, &lines) {
+                stdout.queue(cursor::MoveTo(run.x as u16, self.top + run.y as u16))?;
+                print_cells(&mut stdout, run.cells)?;
+            }
+            stdout.queue(cursor::MoveTo(0, self.top + lines.len() as u16))?;
+        } else {
+            stdout.queue(cursor::MoveTo(0, self.top))?;
+            for line in &lines {
+                // Clearing removes whatever was left over from a wider display.
+                print_cells(&mut stdout, line)?;
+                stdout
+                    .queue(terminal::Clear(ClearType::UntilNewLine))?
+                    .queue(Print("\r\n"))?;
+            }
+            stdout.queue(terminal::Clear(ClearType::FromCursorDown))?;
+        }
+
+        stdout.queue(cursor::Show)?.flush()?;
+        self.lines = lines;
+        Ok(())
+    }
+}
+
+/// Prints cells, only changing colors where they differ from the last
+/// cell's, and then goes back to the terminal's colors.
+fn print_cells(stdout: &mut io::Stdout, cells: &[StyledCell]) -> io::Result<()> {
+    let mut colors = (None, None);
+    for cell in cells {
+        if colors != (cell.foreground, cell.background) {
+            stdout
+                .queue(SetForegroundColor(cell.foreground.unwrap_or(Color::Reset)))?
+                .queue(SetBackgroundColor(cell.background.unwrap_or(Color::Reset)))?;
+            colors = (cell.foreground, cell.background);
+        }
+        stdout.queue(Print(cell.symbol))?;
+    }
+    stdout.queue(ResetColor)?;
+    Ok(())
+}
+
+fn is_same_shape<T>(previous: &[Vec<T>], current: &[Vec<T>]) -> bool {
+    previous.len() == current.len()
+        && previous
+            .iter()
+            .zip(current)
+            .all(|(previous, current)| previous.len() == current.len())
+}
+
+/// The runs of cells in `current` that differ from `previous`, which must
+/// have the same shape.
+pub fn changed_runs<'a>(
+    previous: &[Vec<StyledCell>],
+    current: &'a [Vec<StyledCell>],
+) -> Vec<Run<'a>> {
+    let mut runs = Vec::new();
+    for (y, (previous, current)) in previous.iter().zip(current).enumerate() {
+        let mut x = 0;
+        while x < current.len() {
+            if previous[x] == current[x] {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while x < current.len() && previous[x] != current[x] {
+                x += 1;
+            }
+            runs.push(Run {
+                x: start,
+                y,
+                cells: &current[start..x],
+            });
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+#[path = "./screen_test.rs"]
+mod screen_test;
@@ -0,0 +1,60 @@
+use crossterm::style::Color;
+
+use super::{changed_runs, Run};
+use crate::theme::StyledCell;
+
+fn lines(text: &[&str]) -> Vec<Vec<StyledCell>> {
+    text.iter()
+        .map(|line| {
+            line.chars()
+                .map(|symbol| StyledCell {
+                    symbol,
+                    foreground: None,
+                    background: None,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn finds_changed_runs() {
+    let previous = lines(&["#..#", "....", "##.."]);
+    let current = lines(&["##.#", "....", "..##"]);
+    assert_eq!(
+        changed_runs(&previous, &current),
+        [
+            Run {
+                x: 1,
+                y: 0,
+                cells: &current[0][1..2]
+            },
+            Run {
+                x: 0,
+                y: 2,
+                cells: &current[2][..]
+            },
+        ]
+    );
+}
+
+#[test]
+fn finds_color_changes() {
+    let previous = lines(&["#..#"]);
+    let mut current = previous.clone();
+    current[0][3].foreground = Some(Color::AnsiValue(240));
+    assert_eq!(
+        changed_runs(&previous, &current),
+        [Run {
+            x: 3,
+            y: 0,
+            cells: &current[0][3..]
+        }]
+    );
+}
+
+#[test]
+fn finds_nothing_when_unchanged() {
+    let previous = lines(&["#..#", "...."]);
+    assert_eq!(changed_runs(&previous, &previous.clone()), []);
+}
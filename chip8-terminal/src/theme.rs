@@ -0,0 +1,211 @@
+use std::env;
+
+use crossterm::style::Color;
+
+use crate::render::{Cell, LIT};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    const BLACK: Rgb = Rgb(0, 0, 0);
+    const WHITE: Rgb = Rgb(255, 255, 255);
+
+    /// Parses `#RRGGBB`.
+    pub fn parse(text: &str) -> Option<Rgb> {
+        let hex = text.strip_prefix('#')?;
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+        Some(Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    /// Mixes in `amount` out of `LIT` of `other`.
+    fn mix(self, other: Rgb, amount: u8) -> Rgb {
+        let channel = |from: u8, to: u8| {
+            let (from, to, amount) = (from as u32, to as u32, amount as u32);
+            ((from * (LIT as u32 - amount) + to * amount) / LIT as u32) as u8
+        };
+        Rgb(
+            channel(self.0, other.0),
+            channel(self.1, other.1),
+            channel(self.2, other.2),
+        )
+    }
+
+    /// The nearest color in the xterm 256-color palette, from its 6x6x6 cube
+    /// or its ramp of greys.
+    fn to_ansi_256(self) -> u8 {
+        const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+        let nearest_level = |value: u8| {
+            (0..6)
+                .min_by_key(|index| (CUBE_LEVELS[*index] as i32 - value as i32).abs())
+                .unwrap()
+        };
+        let (r, g, b) = (
+            nearest_level(self.0),
+            nearest_level(self.1),
+            nearest_level(self.2),
+        );
+        let cube = Rgb(CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+        let average = (self.0 as u32 + self.1 as u32 + self.2 as u32) / 3;
+        let grey_index = (average.saturating_sub(3) / 10).min(23) as u8;
+        let grey_level = 8 + grey_index * 10;
+        let grey = Rgb(grey_level, grey_level, grey_level);
+
+        if self.distance(grey) < self.distance(cube) {
+            232 + grey_index
+        } else {
+            16 + 36 * r as u8 + 6 * g as u8 + b as u8
+        }
+    }
+
+    fn distance(self, other: Rgb) -> u32 {
+        let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+        channel(self.0, other.0) + channel(self.1, other.1) + channel(self.2, other.2)
+    }
+}
+
+/// How many colors the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    TrueColor,
+    Ansi256,
+}
+
+impl ColorMode {
+    pub fn from_name(name: &str) -> Option<ColorMode> {
+        match name {
+            "truecolor" => Some(ColorMode::TrueColor),
+            "256" => Some(ColorMode::Ansi256),
+            _ => None,
+        }
+    }
+
+    /// Terminals with true color support say so in `COLORTERM`.
+    pub fn detect() -> ColorMode {
+        match env::var("COLORTERM").as_deref() {
+            Ok("truecolor" | "24bit") => ColorMode::TrueColor,
+            _ => ColorMode::Ansi256,
+        }
+    }
+
+    fn color(&self, rgb: Rgb) -> Color {
+        match self {
+            ColorMode::TrueColor => Color::Rgb {
+                r: rgb.0,
+                g: rgb.1,
+                b: rgb.2,
+            },
+            ColorMode::Ansi256 => Color::AnsiValue(rgb.to_ansi_256()),
+        }
+    }
+}
+
+/// The colors of lit and unlit pixels. `None` leaves the terminal's own
+/// color, which in-between brightnesses take to be white or black.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub foreground: Option<Rgb>,
+    pub background: Option<Rgb>,
+}
+
+const THEMES: [(&str, Theme); 6] = [
+    (
+        "default",
+        Theme {
+            foreground: None,
+            background: None,
+        },
+    ),
+    // A COSMAC VIP on a black and white TV.
+    (
+        "vip",
+        Theme {
+            foreground: Some(Rgb(0xF0, 0xF0, 0xF0)),
+            background: Some(Rgb(0x10, 0x10, 0x10)),
+        },
+    ),
+    // A green phosphor monitor.
+    (
+        "green",
+        Theme {
+            foreground: Some(Rgb(0x33, 0xFF, 0x66)),
+            background: Some(Rgb(0x05, 0x1A, 0x0A)),
+        },
+    ),
+    // An amber phosphor monitor.
+    (
+        "amber",
+        Theme {
+            foreground: Some(Rgb(0xFF, 0xB0, 0x00)),
+            background: Some(Rgb(0x1F, 0x12, 0x00)),
+        },
+    ),
+    // The LCD of an HP 48 calculator, which ran CHIP-48 and SUPER-CHIP.
+    (
+        "hp48",
+        Theme {
+            foreground: Some(Rgb(0x22, 0x2E, 0x22)),
+            background: Some(Rgb(0x9B, 0xAE, 0x8C)),
+        },
+    ),
+    // Octo's default colors.
+    (
+        "octo",
+        Theme {
+            foreground: Some(Rgb(0xFF, 0xCC, 0x00)),
+            background: Some(Rgb(0x99, 0x66, 0x00)),
+        },
+    ),
+];
+
+impl Theme {
+    pub fn from_name(name: &str) -> Option<Theme> {
+        THEMES
+            .iter()
+            .find(|(theme, _)| *theme == name)
+            .map(|(_, theme)| *theme)
+    }
+
+    /// The names of the themes, separated by commas.
+    pub fn names() -> String {
+        let names: Vec<&str> = THEMES.iter().map(|(name, _)| *name).collect();
+        names.join(", ")
+    }
+
+    /// The color for `brightness`, or `None` for the terminal's default.
+    fn color(&self, brightness: u8, mode: ColorMode) -> Option<Color> {
+        match brightness {
+            0 => self.background.map(|rgb| mode.color(rgb)),
+            LIT => self.foreground.map(|rgb| mode.color(rgb)),
+            _ => {
+                let background = self.background.unwrap_or(Rgb::BLACK);
+                let foreground = self.foreground.unwrap_or(Rgb::WHITE);
+                Some(mode.color(background.mix(foreground, brightness)))
+            }
+        }
+    }
+
+    pub fn style(&self, cell: Cell, mode: ColorMode) -> StyledCell {
+        StyledCell {
+            symbol: cell.symbol,
+            foreground: self.color(cell.foreground, mode),
+            background: self.color(cell.background, mode),
+        }
+    }
+}
+
+/// A cell as it's drawn to the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StyledCell {
+    pub symbol: char,
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+}
+
+#[cfg(test)]
+#[path = "./theme_test.rs"]
+mod theme_test;
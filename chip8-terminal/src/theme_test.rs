@@ -0,0 +1,94 @@
+use crossterm::style::Color;
+
+use super::{ColorMode, Rgb, StyledCell, Theme};
+use crate::render::{Cell, LIT};
+
+#[test]
+fn parses_colors() {
+    assert_eq!(Rgb::parse("#FFb000"), Some(Rgb(0xFF, 0xB0, 0x00)));
+    assert_eq!(Rgb::parse("FFB000"), None);
+    assert_eq!(Rgb::parse("#FFB00"), None);
+    assert_eq!(Rgb::parse("#FFB0G0"), None);
+}
+
+#[test]
+fn converts_to_256_colors() {
+    assert_eq!(Rgb(0, 0, 0).to_ansi_256(), 16);
+    assert_eq!(Rgb(255, 255, 255).to_ansi_256(), 231);
+    assert_eq!(Rgb(255, 175, 0).to_ansi_256(), 214);
+    assert_eq!(Rgb(128, 128, 128).to_ansi_256(), 244);
+}
+
+#[test]
+fn styles_cells() {
+    let theme = Theme::from_name("amber").unwrap();
+    let cell = Cell {
+        symbol: '\u{2580}',
+        foreground: LIT,
+        background: 0,
+    };
+    assert_eq!(
+        theme.style(cell, ColorMode::TrueColor),
+        StyledCell {
+            symbol: '\u{2580}',
+            foreground: Some(Color::Rgb {
+                r: 0xFF,
+                g: 0xB0,
+                b: 0x00
+            }),
+            background: Some(Color::Rgb {
+                r: 0x1F,
+                g: 0x12,
+                b: 0x00
+            }),
+        }
+    );
+}
+
+#[test]
+fn fades_towards_the_background() {
+    let theme = Theme {
+        foreground: Some(Rgb(200, 100, 0)),
+        background: Some(Rgb(0, 100, 200)),
+    };
+    let cell = Cell {
+        symbol: '\u{2588}',
+        foreground: 51,
+        background: 0,
+    };
+    assert_eq!(
+        theme.style(cell, ColorMode::TrueColor).foreground,
+        Some(Color::Rgb {
+            r: 40,
+            g: 100,
+            b: 160
+        })
+    );
+}
+
+#[test]
+fn keeps_terminal_colors_by_default() {
+    let theme = Theme::from_name("default").unwrap();
+    let lit = Cell {
+        symbol: '\u{2588}',
+        foreground: LIT,
+        background: 0,
+    };
+    assert_eq!(
+        theme.style(lit, ColorMode::Ansi256),
+        StyledCell {
+            symbol: '\u{2588}',
+            foreground: None,
+            background: None,
+        }
+    );
+    // Fading pixels are drawn as grey, between white and black.
+    let fading = Cell {
+        foreground: 102,
+        ..lit
+    };
+    assert_eq!(
+        theme.style(fading, ColorMode::Ansi256).foreground,
+        Some(Color::AnsiValue(241))
+    );
+}